@@ -1,6 +1,7 @@
 //! Command line arguments & help
 //!
-use clap::Parser;
+use clap::{ArgGroup, Parser};
+use regex::Regex;
 
 /// Command line tool for tracking work time
 #[derive(Parser, Debug)]
@@ -9,11 +10,14 @@ use clap::Parser;
     version,
     about,
     long_about,
+    group(ArgGroup::new("outputs").args(["list", "report", "balance", "export", "invoice"]).multiple(true)),
+    group(ArgGroup::new("renderings").args(["list", "report", "invoice"]).multiple(true)),
+    group(ArgGroup::new("csv_files").args(["import", "export"]).multiple(true)),
     after_help(
         "\
 Arguments:
 
-  <FILENAME>, <LEGACY_IMPORT>
+  <FILENAME>, <LEGACY_IMPORT>, <IMPORT>, <MERGE>, <OUTPUT>
         File path and name.
 
   <START>, <BACK>, <END>
@@ -27,13 +31,23 @@ Arguments:
         y = year    m = month   d = day of month
         H = hour    M = minute
 
+        or relative to now by a leading '+' or '-' and a duration (see
+        <DURATION>) like '-15m', '+1h', '-1d2h30m' or '-0:45' or with a
+        named day like 'today', 'yesterday', 'tomorrow', 'mon' or 'last fri'
+        instead of the date (e.g. 'yesterday,9:00')
+
   <DURATION>
         Duration in one of the following formats:
 
-        H:M         h,fr        h.fr
+        H:M         h,fr        h.fr        e.g. '1:30', '1,5', '1.5'
+        <d>d<h>h<m>m                        e.g. '1h30m', '90m', '1.5h', '1d'
+        P<d>DT<h>H<m>M<s>S (ISO 8601)       e.g. 'PT1H30M', 'P1D'
 
         H = hour    M = minute
         h = hours  fr = fraction of an hour
+        each unit in the latter formats is optional but keeps its order
+
+        A leading '-' makes a duration negative (e.g. '-1h30m').
 
   <MESSAGE>
         Job description text or will ask for if blank
@@ -41,11 +55,13 @@ Arguments:
   <TAGS>
         List of comma separated tag names (omit spaces)
 
-  <LIST>, <REPORT>, <EXPORT>, <LIST_TAGS>
+  <LIST>, <REPORT>, <BALANCE>, <EXPORT>, <INVOICE>, <LIST_TAGS>, <DELETE>, <DELETED>, <UNDELETE>, <LOCK>, <UNLOCK>
         Time or positional range in one of the following formats:
 
         f-t         f-          p         ~C
         s..u        s..         D
+        y-Wn        Wn          y-m       M
+        y-Qq        Qq          Yy
 
         f = from position
         t = to position
@@ -54,10 +70,24 @@ Arguments:
         s = since time (like in <START>)
         u = until time (like in <START>)
         D = single day (like in <START> but without time)
+        y = year (four digits)
+        n = ISO week number
+        m = month (two digits)
+        M = month name (like 'march' or 'mar')
+        q = quarter (1-4)
+
+        or one of the keywords this-week, last-week, this-month, last-month,
+        this-quarter, last-quarter, this-year, last-year and ytd
+
+        or a filter expression which combines ranges, known tags ('#tag' if it
+        looks like a range or is unknown), 'open', 'closed', durations ('>2h',
+        '<=30m') and message searches ('\"text\"' ignoring case or '/regex/')
+        with '&', '|', '!' and parentheses (like
+        'march & (a | b) & !meeting & \"login\"')
 
   <CSV>
         List of comma separated column names (omit spaces)
-        Available columns: start, end, duration, hours, message, tags, pay, rate, resolution
+        Available columns: pos, id, start, end, duration, hours, message, tags, pay, rate, resolution, max_hours
   <RESOLUTION>
         Work time resolution in fractional hours
 
@@ -67,8 +97,26 @@ Arguments:
   <MAX_HOURS>
         Maximum amount of work hours as integer number
 
+  <TARGET>
+        Up to seven comma separated work hours per weekday starting with monday (omit spaces)
+
+  <CLIENT>
+        Client address (separate lines by '\\n')
+
+  <TAX>
+        Tax rate in percent as floating point number
+
+  <INVOICE_NUMBER>
+        Number of the next invoice as integer number (if higher than the last one)
+
   <EDIT>
-        Position of a job to edit.
+        Position or ID (like '@3f2a') of a job to edit.
+
+  <PURGE>
+        Date and time (like in <START>) before which jobs must have been deleted to get purged.
+
+  <UNDO>, <REDO>
+        Number of operations to undo or redo (default is 1).
 "
     ),
     help_template(
@@ -124,17 +172,49 @@ pub struct Args {
     #[arg(short, long, conflicts_with_all(["start","end","back","message","list","edit"]))]
     pub report: Option<Option<String>>,
 
+    /// Print hours compared to target hours with flextime balance of all jobs or selective by position(s) or time(s)
+    #[arg(long, conflicts_with_all(["start","end","back","message","list","report","edit"]))]
+    pub balance: Option<Option<String>>,
+
     /// Export all jobs or selective by position(s) or time(s) as CSV
     #[arg(short='E', long="export", conflicts_with_all(["start","end","back","message","list","report","edit"]))]
     pub export: Option<Option<String>>,
 
-    /// Customize CSV export columns by comma separated list of column names
-    #[arg(
-        long = "csv",
-        requires("export"),
-        default_value = "tags,start,hours,message"
-    )]
-    pub csv: String,
+    /// Create invoice about all unbilled jobs with the given tags (-t) or selective by position(s) or time(s)
+    #[arg(long="invoice", requires("tags"), conflicts_with_all(["start","end","back","message","list","report","export","edit"]))]
+    pub invoice: Option<Option<String>>,
+
+    /// Summarize jobs into the items of an invoice per day or per message
+    #[arg(long = "group-by", requires("invoice"), default_value = "day", value_parser(["day", "message"]))]
+    pub group_by: String,
+
+    /// Write output of list, report, export or invoice into a file (export format is chosen by the file extension)
+    #[arg(short = 'o', long = "output", requires("outputs"))]
+    pub output: Option<String>,
+
+    /// Format of list, report or invoice (default is chosen by the extension of the output file or text)
+    #[arg(long = "format", requires("renderings"), value_parser(["text", "markdown", "html"]))]
+    pub format: Option<String>,
+
+    /// Customize CSV export or import columns by comma separated list of column names [default: tags,start,hours,message]
+    #[arg(long = "csv")]
+    pub csv: Option<String>,
+
+    /// Export as JSON (including positions, IDs, pay and effective configuration) instead of CSV
+    #[arg(long = "json", requires("export"), conflicts_with("csv"))]
+    pub json: bool,
+
+    /// Export as or import from iCalendar (import detects *.ics files also without this option)
+    #[arg(long = "ics", conflicts_with_all(["csv", "json"]))]
+    pub ics: bool,
+
+    /// Export as or import from Timewarrior data
+    #[arg(long = "timewarrior", conflicts_with_all(["csv", "json", "ics"]))]
+    pub timewarrior: bool,
+
+    /// Export as or import from hledger/ledger timeclock
+    #[arg(long = "timeclock", conflicts_with_all(["csv", "json", "ics", "timewarrior"]))]
+    pub timeclock: bool,
 
     /// Show configuration parameters
     #[arg(short='C', long, conflicts_with_all(["start","end","back","message","list","report","edit"]))]
@@ -152,23 +232,153 @@ pub struct Args {
     #[arg(long="max-hours", conflicts_with_all(["start","end","back","message","list","report","edit"]))]
     pub max_hours: Option<u32>,
 
+    /// Set contracted work hours per weekday starting with monday (like '8,8,8,8,6', can be combined with --tags)
+    #[arg(long, conflicts_with_all(["start","end","back","message","list","report","edit"]))]
+    pub target: Option<String>,
+
+    /// Set the client address printed on invoices (separate lines by '\n', can be combined with --tags)
+    #[arg(long, conflicts_with_all(["start","end","back","message","list","report","edit","invoice"]))]
+    pub client: Option<String>,
+
+    /// Set the tax rate in percent added to invoices (can be combined with --tags)
+    #[arg(long, conflicts_with_all(["start","end","back","message","list","report","edit","invoice"]))]
+    pub tax: Option<f64>,
+
+    /// Set the currency sign printed on invoices [default: $] (can be combined with --tags)
+    #[arg(long, conflicts_with_all(["start","end","back","message","list","report","edit","invoice"]))]
+    pub currency: Option<char>,
+
+    /// Set the lowest number of the next invoice (can be combined with --tags)
+    #[arg(long="invoice-number", conflicts_with_all(["start","end","back","message","list","report","edit","invoice"]))]
+    pub invoice_number: Option<u32>,
+
     /// Import jobs from legacy jobber (ruby version)
     #[arg(long="legacy-import", conflicts_with_all(["start","end","back","tags","message","list","report","edit"]))]
     pub legacy_import: Option<String>,
 
+    /// Import jobs from CSV or another format (CSV columns are taken from --csv or the header line)
+    #[arg(long="import", conflicts_with_all(["start","end","back","tags","message","list","report","edit","legacy_import","export"]))]
+    pub import: Option<String>,
+
+    /// Import detailed report CSV of Toggl
+    #[arg(long = "toggl", requires("import"), conflicts_with_all(["csv", "ics", "timewarrior", "timeclock"]))]
+    pub toggl: bool,
+
+    /// Import detailed report CSV of Clockify
+    #[arg(long = "clockify", requires("import"), conflicts_with_all(["csv", "ics", "timewarrior", "timeclock", "toggl"]))]
+    pub clockify: bool,
+
+    /// Delimiter between fields when importing or exporting CSV
+    #[arg(long = "delimiter", requires("csv_files"), default_value = ",")]
+    pub delimiter: char,
+
+    /// Format of date and time when importing or exporting CSV (like '%Y-%m-%d %H:%M')
+    #[arg(
+        long = "date-format",
+        requires("csv_files"),
+        default_value = "%m/%d/%Y %H:%M"
+    )]
+    pub date_format: String,
+
+    /// Decimal separator of numbers when exporting CSV
+    #[arg(long = "decimal-separator", requires("export"), default_value = ".")]
+    pub decimal_separator: char,
+
+    /// Which fields to quote when exporting CSV
+    #[arg(long = "quote", requires("export"), default_value = "strings", value_parser(["all", "strings", "necessary"]))]
+    pub quote: String,
+
+    /// Do not write a header line when exporting CSV
+    #[arg(long = "no-header", requires("export"))]
+    pub no_header: bool,
+
+    /// Time zone of date and time when importing ('local', 'utc' or offset like '+02:00')
+    #[arg(long = "timezone", requires("import"), default_value = "local")]
+    pub timezone: String,
+
+    /// Merge jobs and configuration of another jobber database
+    #[arg(long="merge", conflicts_with_all(["start","end","back","tags","message","list","report","edit","legacy_import"]))]
+    pub merge: Option<String>,
+
     /// List all known tags (may use -t to filter by super tag)
     #[arg(short='T', long="list-tags", conflicts_with_all(["start","end","back","message","list","report","edit"]))]
     pub list_tags: Option<Option<String>>,
 
-    /// Edit some items of a job by it's position
+    /// Edit some items of a job by its position or ID
     #[arg(long="edit", conflicts_with_all(["back","list","report","delete"]))]
-    pub edit: Option<Option<usize>>,
+    pub edit: Option<Option<String>>,
 
-    /// Delete some jobs by it's position
+    /// Delete some jobs by its position
     #[arg(long="delete", conflicts_with_all(["start","back","end","list","report","edit"]))]
     pub delete: Option<String>,
 
+    /// List deleted jobs or selective by position(s) or time(s)
+    #[arg(long="deleted", conflicts_with_all(["start","end","back","message","list","report","edit","delete"]))]
+    pub deleted: Option<Option<String>>,
+
+    /// Restore deleted jobs by its position
+    #[arg(long="undelete", conflicts_with_all(["start","back","end","list","report","edit","delete","deleted"]))]
+    pub undelete: Option<String>,
+
+    /// Lock all finished jobs or selective by position(s) or time(s) to protect them from editing and deletion
+    #[arg(long="lock", conflicts_with_all(["start","back","end","list","report","edit","delete","undelete","unlock"]))]
+    pub lock: Option<Option<String>>,
+
+    /// Unlock all locked jobs or selective by position(s) or time(s)
+    #[arg(long="unlock", conflicts_with_all(["start","back","end","list","report","edit","delete","undelete"]))]
+    pub unlock: Option<Option<String>>,
+
+    /// Permanently remove all deleted jobs or the ones which were deleted before the given time
+    #[arg(long="purge", conflicts_with_all(["start","back","end","list","report","edit","delete","deleted","undelete"]))]
+    pub purge: Option<Option<String>>,
+
+    /// Undo the last (or the given number of) database modification(s)
+    #[arg(long="undo", conflicts_with_all(["start","back","end","list","report","edit","delete","redo"]))]
+    pub undo: Option<Option<usize>>,
+
+    /// Redo the last (or the given number of) undone database modification(s)
+    #[arg(long="redo", conflicts_with_all(["start","back","end","list","report","edit","delete","undo"]))]
+    pub redo: Option<Option<usize>>,
+
     /// Run dry (don't write any changes into database)
     #[arg(short = 'D', long = "dry")]
     pub dry: bool,
+
+    /// Wait until the database is no longer locked by another jobber process
+    #[arg(long = "wait")]
+    pub wait: bool,
+}
+
+impl Args {
+    /// Parse arguments after attaching negative time offsets to their option.
+    pub fn parse_offsets<I, T>(args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: ToString,
+    {
+        Self::parse_from(attach_offsets(args))
+    }
+}
+
+/// Attach negative time offsets and durations (like `-15m` or `-PT1H`) to the preceding option
+/// (`-s -15m` becomes `-s=-15m`) because otherwise they would be taken for an option.
+fn attach_offsets<I, T>(args: I) -> Vec<String>
+where
+    I: IntoIterator<Item = T>,
+    T: ToString,
+{
+    let re = Regex::new(r"(?i)^-(\d|[.,]\d|pt?\d)").unwrap();
+    let mut result: Vec<String> = Vec::new();
+    for arg in args.into_iter().map(|arg| arg.to_string()) {
+        match result.last_mut() {
+            Some(option)
+                if re.is_match(&arg) && option.starts_with('-') && !option.contains('=') =>
+            {
+                option.push('=');
+                option.push_str(&arg);
+            }
+            _ => result.push(arg),
+        }
+    }
+    result
 }
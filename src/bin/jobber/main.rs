@@ -9,7 +9,6 @@ mod args;
 mod tests;
 
 use args::Args;
-use clap::Parser;
 use jobberdb::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +22,22 @@ const ASK_FOR_MESSAGE: &str = "You need to enter a message about what you did to
 #[derive(Debug, Serialize, Deserialize)]
 struct Config {
     database: String,
+    /// Number of database backups to keep when saving.
+    #[serde(default = "default_backups")]
+    backups: usize,
+    /// Maximum number of operations kept for undo.
+    #[serde(default = "default_journal")]
+    journal: usize,
+}
+
+/// default for `backups` in `Config`
+fn default_backups() -> usize {
+    3
+}
+
+/// default for `journal` in `Config`
+fn default_journal() -> usize {
+    JOURNAL_LIMIT
 }
 
 impl Default for Config {
@@ -33,13 +48,17 @@ impl Default for Config {
             ".".to_string()
         };
         let path = format!("{}/jobber.json", home);
-        Self { database: path }
+        Self {
+            database: path,
+            backups: default_backups(),
+            journal: default_journal(),
+        }
     }
 }
 
 /// Main which just catches errors.
 fn main() {
-    let args = Args::parse();
+    let args = Args::parse_offsets(std::env::args());
     let context = Context::now();
     if let Err(err) = run(&mut std::io::stdout(), args, Checks::all(), &context) {
         #[cfg(feature = "colors")]
@@ -65,31 +84,22 @@ fn run<W: std::io::Write>(
 ) -> Result<(), Error> {
     let dry = args.dry;
 
-    // get filename from config or arguments
-    let filename = if let Some(filename) = &args.filename {
-        filename.clone()
+    // get filename (and number of backups and journal limit) from arguments or config
+    let (filename, backups, journal) = if let Some(filename) = &args.filename {
+        (filename.clone(), default_backups(), default_journal())
     } else {
         let cfg: Config = confy::load("jobber", "config").map_err(Error::Confy)?;
-        cfg.database
+        (cfg.database, cfg.backups, cfg.journal)
     };
 
+    // lock database until we are done (also while the user is asked because any
+    // confirmation or position refers to the database like it was loaded)
+    let _lock = Lock::acquire(&filename, args.wait)?;
+
     // load database from file or create new
-    let mut jobs = match Jobs::load(&filename) {
-        Ok(jobs) => {
-            eprintln!(
-                "Loaded database ({} entries) from file '{filename}'",
-                jobs.count()
-            );
-            jobs
-        }
-        Err(Error::Io(_)) => {
-            eprintln!("Beginning new database file '{filename}'");
-            Jobs::new()
-        }
-        Err(err) => {
-            return Err(err);
-        }
-    };
+    let mut storage = jobberdb::storage::open(&filename, backups)?;
+    let mut jobs = load(storage.as_mut(), &filename)?;
+    jobs.set_journal_limit(journal);
 
     // parse arguments into a command
     let mut command = parse(args, jobs.open_start(), context)?;
@@ -125,8 +135,8 @@ fn run<W: std::io::Write>(
             command.set_message(enter(ASK_FOR_MESSAGE)?);
             jobs.process(w, &command, Checks::omit(), context)
         }
-        Err(Error::OutputFileExists(filename)) => {
-            eprintln!("{}", Error::OutputFileExists(filename));
+        Err(Error::OutputFileExists(output)) => {
+            eprintln!("{}", Error::OutputFileExists(output));
             if ask("Do you want to overwrite the existing file?", false)? {
                 jobs.process(w, &command, Checks::omit(), context)
             } else {
@@ -162,13 +172,37 @@ fn run<W: std::io::Write>(
         if dry {
             eprintln!("DRY RUN: Changes were NOT saved into database file '{filename}'!");
         } else {
-            jobs.save(&filename)?;
+            jobs.store(storage.as_mut())?;
             eprintln!("Saved database into file '{filename}'");
         }
     }
     Ok(())
 }
 
+/// Load database from storage or create a new one if the database file does not exist.
+fn load(storage: &mut dyn Storage, filename: &str) -> Result<Jobs, Error> {
+    match storage.load() {
+        Ok(jobs) => {
+            eprintln!(
+                "Loaded database ({} entries) from file '{filename}'",
+                jobs.count()
+            );
+            if let Some(schema) = jobs.upgraded() {
+                eprintln!(
+                    "Upgraded database '{filename}' from schema version {schema} to {}",
+                    schema::SCHEMA
+                );
+            }
+            Ok(jobs)
+        }
+        Err(Error::Io(_)) => {
+            eprintln!("Beginning new database file '{filename}'");
+            Ok(Jobs::new())
+        }
+        Err(err) => Err(err),
+    }
+}
+
 /// Run argument line on given database (or create one) and return the resulting database it.
 #[cfg(test)]
 pub fn run_line<W: std::io::Write>(
@@ -183,7 +217,7 @@ pub fn run_line<W: std::io::Write>(
     } else {
         Jobs::new()
     };
-    run_line_mut(w,line, &mut jobs, checks, context)?;
+    run_line_mut(w, line, &mut jobs, checks, context)?;
     Ok(jobs)
 }
 
@@ -214,7 +248,11 @@ pub fn run_line_mut<W: std::io::Write>(
     checks: Checks,
     context: &Context,
 ) -> Result<Operation, Error> {
-    let command = parse(Args::parse_from(line.split_ascii_whitespace()), None, context)?;
+    let command = parse(
+        Args::parse_offsets(line.split_ascii_whitespace()),
+        None,
+        context,
+    )?;
     jobs.process(w, &command, checks, context)
 }
 
@@ -227,7 +265,7 @@ pub fn run_args_mut<W: std::io::Write>(
     checks: Checks,
     context: &Context,
 ) -> Result<Operation, Error> {
-    let command = parse(Args::parse_from(args), None, context)?;
+    let command = parse(Args::parse_offsets(args), None, context)?;
     jobs.process(w, &command, checks, context)
 }
 
@@ -248,9 +286,7 @@ fn ask(question: &str, default_yes: bool) -> Result<bool, Error> {
     eprintln!("{} ({})", question, if default_yes { "Y/n" } else { "y/N" });
 
     let mut buffer = String::new();
-    std::io::stdin()
-        .read_line(&mut buffer)
-        .map_err(Error::Io)?;
+    std::io::stdin().read_line(&mut buffer).map_err(Error::Io)?;
 
     Ok(match buffer.trim_end().to_lowercase().as_str() {
         "y" | "yes" => true,
@@ -273,13 +309,11 @@ fn enter(question: &str) -> Result<String, Error> {
 
     #[cfg(not(feature = "colors"))]
     eprintln!("{}", question);
-    
+
     let mut result = String::new();
     loop {
         let mut buffer = String::new();
-        std::io::stdin()
-            .read_line(&mut buffer)
-            .map_err(Error::Io)?;
+        std::io::stdin().read_line(&mut buffer).map_err(Error::Io)?;
 
         let line = buffer.trim_end();
         result += line;
@@ -300,11 +334,16 @@ fn enter(question: &str) -> Result<String, Error> {
 /// * `line` - argument line to parse
 /// * `open_start` - if data base has an open job this shall give its starting time
 /// * `context` - reality
-pub fn parse_line(line :&str,
+pub fn parse_line(
+    line: &str,
     open_start: Option<DateTime>,
     context: &Context,
 ) -> Result<Command, Error> {
-    parse(Args::parse_from(line.split_whitespace()),open_start,context)
+    parse(
+        Args::parse_offsets(line.split_whitespace()),
+        open_start,
+        context,
+    )
 }
 
 /// Parse arguments into a command.
@@ -326,17 +365,17 @@ pub fn parse(
     // 1) parse everything from arguments...
 
     let start = if let Some(start) = args.start {
-        Some(PartialDateTime::parse(start)?)
+        Some(PartialDateTime::parse(start, context)?)
     } else {
         None
     };
     let back = if let Some(back) = args.back {
-        Some(PartialDateTime::parse(back)?)
+        Some(PartialDateTime::parse(back, context)?)
     } else {
         None
     };
     let end = if let Some(end) = args.end {
-        Some(PartialDateTime::parse(end)?)
+        Some(PartialDateTime::parse(end, context)?)
     } else {
         None
     };
@@ -362,17 +401,91 @@ pub fn parse(
     } else {
         None
     };
+    let balance = if let Some(balance) = args.balance {
+        Some(Range::parse(balance, context)?)
+    } else {
+        None
+    };
+    let invoice = if let Some(invoice) = args.invoice {
+        Some(Range::parse(invoice, context)?)
+    } else {
+        None
+    };
+    let output = args.output;
+    // lower case extension of the output file
+    let extension = output
+        .as_deref()
+        .and_then(|output| std::path::Path::new(output).extension())
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    // choose list and report format by option or by extension of the output file
+    let format = match args.format.as_deref().unwrap_or(extension.as_str()) {
+        "markdown" | "md" => OutputFormat::Markdown,
+        "html" | "htm" => OutputFormat::Html,
+        _ => OutputFormat::Text,
+    };
     let csv = args.csv;
+    let json = args.json;
+    let ics = args.ics;
+    let timewarrior = args.timewarrior;
+    let timeclock = args.timeclock;
+    let zone = Zone::parse(&args.timezone)?;
+    let dialect = CsvDialect {
+        delimiter: args.delimiter,
+        date_format: args.date_format,
+        zone: zone.clone(),
+        decimal_separator: args.decimal_separator,
+        quoting: match args.quote.as_str() {
+            "all" => Quoting::All,
+            "necessary" => Quoting::Necessary,
+            _ => Quoting::Strings,
+        },
+        header: !args.no_header,
+    };
+    let import = if let Some(filename) = args.import {
+        // choose format by option or file extension
+        let format = if args.toggl || args.clockify {
+            ImportFormat::Tracker {
+                tracker: if args.toggl {
+                    Tracker::Toggl
+                } else {
+                    Tracker::Clockify
+                },
+                dialect: dialect.clone(),
+            }
+        } else if timewarrior {
+            ImportFormat::Timewarrior
+        } else if timeclock {
+            ImportFormat::Timeclock { zone }
+        } else if ics || filename.to_lowercase().ends_with(".ics") {
+            ImportFormat::Ics { zone }
+        } else {
+            ImportFormat::Csv {
+                dialect: dialect.clone(),
+                columns: csv.as_deref().map(Columns::parse).transpose()?,
+            }
+        };
+        Some((filename, format))
+    } else {
+        None
+    };
 
     // configuration items
     let resolution = args.resolution;
     let rate = args.rate;
     let max_hours = args.max_hours;
+    // lines of the client address may be separated by '\n'
+    let client = args.client.map(|client| client.replace("\\n", "\n"));
+    let tax = args.tax;
+    let currency = args.currency;
+    let invoice_number = args.invoice_number;
+    let target = args.target.as_deref().map(Target::parse).transpose()?;
     // true if any of the configuration items is available
     let configuration = args.configuration;
 
     // import old jobber CSV
     let legacy_import = args.legacy_import;
+    let merge = args.merge;
 
     let list_tags = if let Some(list_tags) = args.list_tags {
         Some(Range::parse(list_tags, context)?)
@@ -382,8 +495,7 @@ pub fn parse(
 
     let edit = if let Some(edit) = args.edit {
         if let Some(edit) = edit {
-            // de-humanize position
-            Some(Some(edit - 1))
+            Some(Some(JobRef::parse(&edit)?))
         } else {
             Some(None)
         }
@@ -395,10 +507,44 @@ pub fn parse(
     } else {
         None
     };
+    let deleted = if let Some(deleted) = args.deleted {
+        Some(Range::parse(deleted, context)?)
+    } else {
+        None
+    };
+    let undelete = if let Some(undelete) = args.undelete {
+        Some(Range::parse(Some(undelete), context)?)
+    } else {
+        None
+    };
+    let lock = if let Some(lock) = args.lock {
+        Some(Range::parse(lock, context)?)
+    } else {
+        None
+    };
+    let unlock = if let Some(unlock) = args.unlock {
+        Some(Range::parse(unlock, context)?)
+    } else {
+        None
+    };
+    let purge = if let Some(purge) = args.purge {
+        Some(match PartialDateTime::parse(purge, context)? {
+            PartialDateTime::None => None,
+            before => Some(before.into(context.time())),
+        })
+    } else {
+        None
+    };
+    let undo = args.undo.map(|count| count.unwrap_or(1));
+    let redo = args.redo.map(|count| count.unwrap_or(1));
 
     // 2) create command depending on what arguments were given...
 
-    Ok(if let Some(pos) = edit {
+    let command = if let Some(count) = undo {
+        Command::Undo { count }
+    } else if let Some(count) = redo {
+        Command::Redo { count }
+    } else if let Some(pos) = edit {
         if let Some(start) = start {
             let mut start = start.into(context.time());
             if let Some(end) = end {
@@ -468,6 +614,16 @@ pub fn parse(
         }
     } else if let Some(range) = delete {
         Command::Delete { range, tags }
+    } else if let Some(range) = undelete {
+        Command::Undelete { range, tags }
+    } else if let Some(range) = deleted {
+        Command::ListDeleted { range, tags }
+    } else if let Some(range) = lock {
+        Command::Lock { range, tags }
+    } else if let Some(range) = unlock {
+        Command::Unlock { range, tags }
+    } else if let Some(before) = purge {
+        Command::Purge { before }
     } else if let Some(start) = start {
         let mut start = start.into(context.time());
         if let Some(end) = end {
@@ -562,31 +718,109 @@ pub fn parse(
         };
         Command::End { end, message, tags }
     } else if let Some(range) = list {
-        Command::List { range, tags }
-    } else if let Some(range) = export {
-        Command::ExportCSV {
+        Command::List {
             range,
             tags,
-            columns: csv,
+            format,
+        }
+    } else if let Some(range) = export {
+        // choose format by option or by extension of the output file
+        let format = if json {
+            "json"
+        } else if ics {
+            "ics"
+        } else if timewarrior {
+            "data"
+        } else if timeclock {
+            "timeclock"
+        } else if csv.is_some() {
+            "csv"
+        } else {
+            extension.as_str()
+        };
+        match format {
+            "json" => Command::ExportJSON { range, tags },
+            "ics" => Command::ExportICS { range, tags },
+            "data" => Command::ExportTimewarrior { range, tags },
+            "timeclock" => Command::ExportTimeclock { range, tags },
+            _ => {
+                let columns = csv.unwrap_or("tags,start,hours,message".into());
+                // fail early on unknown columns
+                Columns::parse(&columns)?;
+                Command::ExportCSV {
+                    range,
+                    tags,
+                    columns,
+                    dialect,
+                }
+            }
         }
     } else if let Some(range) = report {
-        Command::Report { range, tags }
+        Command::Report {
+            range,
+            tags,
+            format,
+        }
+    } else if let Some(range) = balance {
+        Command::Balance { range, tags }
+    } else if let Some(range) = invoice {
+        Command::Invoice {
+            range,
+            tags: tags
+                .filter(|tags| !tags.is_empty())
+                .ok_or(Error::MissingTags)?,
+            grouping: match args.group_by.as_str() {
+                "message" => Grouping::Message,
+                _ => Grouping::Day,
+            },
+            format,
+        }
     } else if configuration {
         Command::ShowConfiguration
-    } else if resolution.is_some() || rate.is_some() || max_hours.is_some() {
+    } else if resolution.is_some()
+        || rate.is_some()
+        || max_hours.is_some()
+        || client.is_some()
+        || tax.is_some()
+        || currency.is_some()
+        || invoice_number.is_some()
+        || target.is_some()
+    {
         Command::SetConfiguration {
             tags,
             update: Properties {
                 resolution,
                 rate,
                 max_hours,
+                client,
+                tax,
+                currency,
+                invoice: invoice_number,
+                target,
             },
         }
     } else if let Some(filename) = legacy_import {
-        Command::LegacyImport { filename }
+        Command::Import {
+            filename,
+            format: ImportFormat::Legacy,
+        }
+    } else if let Some((filename, format)) = import {
+        Command::Import { filename, format }
+    } else if let Some(filename) = merge {
+        Command::Merge { filename }
     } else if let Some(range) = list_tags {
         Command::ListTags { range, tags }
     } else {
         Command::Intro
+    };
+
+    // 3) redirect output into a file if wanted
+    Ok(if let Some(filename) = output {
+        Command::Output {
+            filename,
+            command: Box::new(command),
+        }
+    } else {
+        command
     })
 }
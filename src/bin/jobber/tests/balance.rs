@@ -0,0 +1,174 @@
+//! Testing option `--balance`.
+
+use crate::*;
+
+/// Report flextime balance against target hours.
+///
+/// - [x] check argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_balance() {
+    let context = Context::new_test("2023-2-8 12:00");
+    let mut jobs = Jobs::new();
+
+    // balance needs target hours
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --balance",
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::MissingTarget)
+    ));
+
+    for line in [
+        "jobber --target 8,8,8,8,6",
+        "jobber -s 1.2.,8:00 -e 16:00 -m wednesday",
+        "jobber -s 2.2.,8:00 -e 18:00 -m thursday",
+        "jobber -s 3.2.,8:00 -e 12:00 -m friday",
+        "jobber -s 6.2.,8:00 -e 16:00 -m monday",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --balance",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"                  2/2023                   
+Day    Actual    Target      Diff   Balance
+  1        22        22        +0        +0
+  5         8        24       -16       -16
+ 12         0         0        +0       -16
+ 19         0         0        +0       -16
+ 26         0         0        +0       -16
+             Feb 2023: 30 of 46 hours (-16)
+
+Total: 30 of 46 hours, flextime balance -16 hours
+"#
+    );
+}
+
+/// Count target hours also in months without any jobs and only until the end of the range.
+///
+/// - [x] check argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_balance_empty_month() {
+    let context = Context::new_test("2023-3-1 18:00");
+    let mut jobs = Jobs::new();
+    for line in [
+        "jobber --target 8,8,8,8,8",
+        "jobber -s 2.1.,8:00 -e 16:00 -m january",
+        "jobber -s 1.3.,8:00 -e 16:00 -m march",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --balance",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Feb 2023: 0 of 160 hours (-160)"));
+    assert!(output.ends_with("Total: 16 of 344 hours, flextime balance -328 hours\n"));
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --balance january",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("Total: 8 of 176 hours, flextime balance -168 hours\n"));
+}
+
+/// Count target hours from the start of the range and take target hours of filtered tags.
+///
+/// - [x] check argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_balance_range_start() {
+    let context = Context::new_test("2023-3-10 18:00");
+    let mut jobs = Jobs::new();
+    for line in [
+        "jobber --target 8,8,8,8,8",
+        "jobber -t parttime --target 4,4,4,4,4",
+        "jobber -s 6.3.,8:00 -e 16:00 -m monday -t parttime",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    // the first job is on the 6th but the month starts on wednesday the 1st
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --balance march",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("Total: 8 of 64 hours, flextime balance -56 hours\n"));
+
+    // target hours of the filtered tag
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --balance march -t parttime",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("Total: 8 of 32 hours, flextime balance -24 hours\n"));
+}
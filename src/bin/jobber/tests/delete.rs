@@ -58,16 +58,19 @@ fn test_delete() {
 
     assert_eq!(
         clean(&output),
-        r#"
+        format!(
+            r#"
     Pos: 2
+     ID: {id}
   Start: Wed Feb 01 2023, 11:00
     End: Wed Feb 01 2023, 12:30
   Hours: 1.5 +-
 Message: second-job
    Tags:  tag 
 
-"#
-        .to_string()
+"#,
+            id = jobs[1].id
+        )
     );
 
     // check if deleted job is removed from report output
@@ -88,7 +91,7 @@ Day     Sun     Mon     Tue     Wed     Thu     Fri     Sat    Week
   5       -       -       -       -       -       -       -       0
  12       -       -       -       -       -       -       -       0
  19       -       -       -       -       -       -       -       0
- 26       -       -                                               0
+ 26       -       -       -                                       0
                                                 Feb 2023: 1.5 hours
 
 Total: 1 job(s), 1.5 hours
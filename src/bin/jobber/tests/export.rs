@@ -39,3 +39,146 @@ fn test_csv_date() {
         .to_string()
     );
 }
+
+/// Export database to JSON.
+///
+/// - [x] checks argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_json() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut output = Vec::new();
+    let mut jobs = Jobs::new();
+    run_line_mut(
+        &mut output,
+        "jobber --rate 100 -t meeting",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut output,
+        "jobber -s 8:00 -d 1:30 -m one -t meeting",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut output,
+        "jobber -s 10:00 -d 2:00 -m two",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -E --json",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+
+    let export: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(export["schema"], 1);
+    assert_eq!(export["configuration"]["tags"]["meeting"]["rate"], 100.0);
+    assert_eq!(export["jobs"][0]["pos"], 1);
+    assert_eq!(export["jobs"][0]["id"], jobs[0].id.as_str());
+    assert_eq!(export["jobs"][0]["hours"], 1.5);
+    assert_eq!(export["jobs"][0]["pay"], 150.0);
+    assert_eq!(export["jobs"][0]["properties"]["tag"], "meeting");
+    assert_eq!(
+        export["jobs"][0]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        [
+            "client",
+            "currency",
+            "invoice",
+            "max_hours",
+            "rate",
+            "resolution",
+            "tag",
+            "target",
+            "tax"
+        ]
+    );
+    assert_eq!(export["jobs"][1]["pay"], serde_json::Value::Null);
+    assert!(export["jobs"][1].get("deleted").is_none());
+    assert_eq!(export["total"]["hours"], 3.5);
+    assert_eq!(export["total"]["pay"], 150.0);
+
+    // filter by tag
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -E --json -t meeting",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let export: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(export["jobs"].as_array().unwrap().len(), 1);
+}
+
+/// Export database to CSV with custom dialect.
+///
+/// - [x] checks argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_csv_dialect() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+    // message with delimiter, quotes and a line break
+    jobs.process(
+        &mut std::io::stdout(),
+        &Command::Add {
+            start: "2023-2-1 8:00".into(),
+            end: "2023-2-1 10:15".into(),
+            message: Some(Some("first line; \"quoted\"\nsecond line".into())),
+            tags: None,
+        },
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    let args = [
+        "jobber",
+        "-E",
+        "--csv",
+        "pos,start,hours,message",
+        "--delimiter",
+        ";",
+        "--decimal-separator",
+        ",",
+        "--date-format",
+        "%Y-%m-%d %H:%M",
+        "--quote",
+        "necessary",
+    ];
+    let mut output = Vec::new();
+    run_args_mut(&mut output, &args, &mut jobs, Checks::omit(), &context).unwrap();
+    assert_eq!(
+        clean(&output),
+        "Position;Start;Hours;Message\n\
+         1;2023-02-01 08:00;2,25;\"first line; \"\"quoted\"\"\nsecond line\"\n"
+    );
+
+    let mut args = args.to_vec();
+    args.push("--no-header");
+    let mut output = Vec::new();
+    run_args_mut(&mut output, &args, &mut jobs, Checks::omit(), &context).unwrap();
+    assert!(clean(&output).starts_with("1;"));
+}
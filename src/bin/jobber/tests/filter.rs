@@ -0,0 +1,129 @@
+//! Testing filter expressions.
+
+use crate::*;
+
+/// Select jobs by filter expressions with `-l`, `-r`, `-E`, `-T` and `--delete`.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_filter() {
+    let context = Context::new_test("2023-4-3 12:00");
+    let mut jobs = Jobs::new();
+
+    for line in [
+        "jobber -s 1.3.,8:00 -e 9:00 -m a -t client_a",
+        "jobber -s 2.3.,8:00 -e 12:00 -m b -t client_b,meeting",
+        "jobber -s 3.3.,8:00 -e 9:00 -m c -t client_c",
+        "jobber -s 1.4.,8:00 -e 9:00 -m d -t client_a",
+        "jobber -s 3.4.,8:00 -m e -t client_b",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    let positions = |jobs: &mut Jobs, args: &[&str]| match run_args_mut(
+        &mut std::io::stdout(),
+        args,
+        jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap()
+    {
+        Operation::List(positions, ..)
+        | Operation::Report(positions, ..)
+        | Operation::ExportCSV(positions, ..)
+        | Operation::Delete(positions) => positions.to_string(),
+        operation => panic!("unexpected operation {operation:?}"),
+    };
+
+    assert_eq!(
+        positions(
+            &mut jobs,
+            &["jobber", "-l", "march & (client_a | client_b)"]
+        ),
+        "1-2"
+    );
+    assert_eq!(
+        positions(
+            &mut jobs,
+            &["jobber", "-r", "client_a | client_b & !meeting"]
+        ),
+        "1,4-5"
+    );
+    assert_eq!(
+        positions(&mut jobs, &["jobber", "-l", "client_b & open"]),
+        "5"
+    );
+    assert_eq!(
+        positions(
+            &mut jobs,
+            &["jobber", "-E", ">=2h & closed", "--csv", "pos"]
+        ),
+        "2"
+    );
+    assert!(matches!(
+        run_args_mut(
+            &mut std::io::stdout(),
+            &["jobber", "-T", "~2 & !open"],
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Ok(Operation::ListTags(tags)) if tags == TagSet::from("client_a")
+    ));
+
+    // delete all of march but the meeting
+    assert_eq!(
+        positions(&mut jobs, &["jobber", "--delete", "2023-03 & !meeting"]),
+        "1,3"
+    );
+    assert!(jobs[0].is_deleted());
+    assert!(!jobs[1].is_deleted());
+
+    // syntax errors are reported
+    assert!(matches!(
+        run_args_mut(
+            &mut std::io::stdout(),
+            &["jobber", "-l", "(march & open"],
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::FilterFormat(..))
+    ));
+
+    // misspelled words are not taken as tags
+    assert!(matches!(
+        run_args_mut(
+            &mut std::io::stdout(),
+            &["jobber", "-l", "!marhc"],
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::FilterFormat(..))
+    ));
+    assert_eq!(positions(&mut jobs, &["jobber", "-l", "!#marhc"]), "2,4-5");
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -t billable --rate 10",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        positions(&mut jobs, &["jobber", "-l", "!billable"]),
+        "2,4-5"
+    );
+}
@@ -0,0 +1,84 @@
+//! Testing options `-E --ics` and `--import` of iCalendar files.
+
+use crate::*;
+
+/// Export jobs into an iCalendar and import them into another database.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_ics() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!("jobber-test-ics-{}.ics", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+
+    let mut jobs = Jobs::new();
+    for line in [
+        "jobber -s 8:00 -e 10:30 -m first-job -t meeting",
+        "jobber -s 11:00 -e 11:45 -m second-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -E --ics",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    std::fs::write(&filename, output).unwrap();
+
+    // import into database with an overlapping job
+    let mut imported = Jobs::new();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 11:30 -e 12:00 -m overlapping-job",
+        &mut imported,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    match run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename}"),
+        &mut imported,
+        Checks::all(),
+        &context,
+    ) {
+        Err(Error::Warnings(warnings)) => {
+            assert_eq!(warnings.len(), 2);
+            assert!(matches!(warnings[0], Warning::Overlaps { .. }));
+            assert!(matches!(warnings[1], Warning::UnknownTags(_)));
+        }
+        _ => panic!("expected warnings"),
+    }
+    assert_eq!(imported.count(), 1);
+
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename}"),
+        &mut imported,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(imported.count(), 3);
+    for pos in 0..2 {
+        assert_eq!(imported[pos + 1], jobs[pos]);
+    }
+
+    std::fs::remove_file(&filename).unwrap();
+}
@@ -0,0 +1,215 @@
+//! Testing option `--import`.
+
+use super::clean;
+use crate::*;
+
+/// Export jobs into CSV and import them into another database.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_import_exported() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!("jobber-test-import-{}.csv", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+
+    let mut jobs = Jobs::new();
+    for line in [
+        "jobber -s 8:00 -e 10:30 -m first-job -t tag1,tag2",
+        "jobber -s 11:00 -e 11:45 -m second-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -E --csv id,start,end,message,tags",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    std::fs::write(&filename, output).unwrap();
+
+    // import into new database
+    let mut imported = Jobs::new();
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename}"),
+        &mut imported,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(imported.count(), 2);
+    for pos in 0..2 {
+        assert_eq!(imported[pos], jobs[pos]);
+    }
+
+    // importing the same IDs again fails
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --import {filename}"),
+            &mut imported,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::Import(_, _))
+    ));
+
+    // importing the same ID twice from one file fails
+    let csv = std::fs::read_to_string(&filename).unwrap();
+    let line = csv.lines().nth(1).unwrap();
+    std::fs::write(&filename, format!("{csv}{line}\n")).unwrap();
+    match run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename}"),
+        &mut Jobs::new(),
+        Checks::omit(),
+        &context,
+    ) {
+        Err(Error::Import(_, errors)) => assert_eq!(errors.len(), 1),
+        _ => panic!("duplicate ID was imported"),
+    }
+
+    std::fs::remove_file(&filename).unwrap();
+}
+
+/// Import CSV with custom dialect and report erroneous lines.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_import_dialect() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!(
+            "jobber-test-import-dialect-{}.csv",
+            std::process::id()
+        ))
+        .to_string_lossy()
+        .to_string();
+    std::fs::write(
+        &filename,
+        "2023-01-30 08:00;2:30;job one;client\n\
+         2023-01-31 08:00;1,5;job two;\n",
+    )
+    .unwrap();
+
+    let args = [
+        "jobber",
+        "--import",
+        &filename,
+        "--csv",
+        "start,duration,message,tags",
+        "--delimiter",
+        ";",
+        "--date-format",
+        "%Y-%m-%d %H:%M",
+        "--timezone",
+        "utc",
+    ];
+    let mut jobs = Jobs::new();
+    let operation = run_args_mut(
+        &mut std::io::stdout(),
+        &args,
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        clean(&operation.to_string().into_bytes()),
+        format!("Imported 2 jobs from {filename} (added new tags  client )."),
+    );
+    assert_eq!(jobs.count(), 2);
+    assert_eq!(jobs[0].start.to_rfc3339(), "2023-01-30T08:00:00+00:00");
+    assert_eq!(
+        jobs[1].end,
+        Some(jobs[1].start + Duration::parse("1.5".into()).unwrap())
+    );
+
+    // broken line is reported
+    std::fs::write(&filename, "2023-01-30 08:00;2:30;job one;client\nbroken\n").unwrap();
+    match run_args_mut(
+        &mut std::io::stdout(),
+        &args,
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    ) {
+        Err(Error::Import(_, errors)) => assert_eq!(errors.len(), 1),
+        _ => panic!("expected import error"),
+    }
+    assert_eq!(jobs.count(), 2);
+
+    std::fs::remove_file(&filename).unwrap();
+}
+
+/// Import Toggl export and confirm new tags.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_import_toggl() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!(
+            "jobber-test-import-toggl-{}.csv",
+            std::process::id()
+        ))
+        .to_string_lossy()
+        .to_string();
+    std::fs::write(
+        &filename,
+        "Client,Project,Description,Start date,Start time,End date,End time,Duration,Tags\n\
+         ACME,Web Site,Fix menu,2023-01-31,08:00:00,2023-01-31,10:30:00,02:30:00,\n\
+         ACME,Web Site,Review,2023-01-31,11:00:00,,,00:45:00,\n",
+    )
+    .unwrap();
+
+    let mut jobs = Jobs::new();
+    match run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename} --toggl"),
+        &mut jobs,
+        Checks::all(),
+        &context,
+    ) {
+        Err(Error::Warnings(warnings)) => match &warnings[..] {
+            [Warning::UnknownTags(tags)] => assert_eq!(*tags, TagSet::from("ACME,Web-Site")),
+            _ => panic!("expected one warning about unknown tags"),
+        },
+        _ => panic!("expected warning about unknown tags"),
+    }
+    assert_eq!(jobs.count(), 0);
+
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename} --toggl"),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 2);
+    assert_eq!(jobs[1].end, Some("2023-01-31 11:45".into()));
+
+    std::fs::remove_file(&filename).unwrap();
+}
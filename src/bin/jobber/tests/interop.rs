@@ -0,0 +1,126 @@
+//! Testing options `--timewarrior` and `--timeclock`.
+
+use super::clean;
+use crate::*;
+
+/// Export jobs into Timewarrior and timeclock format and import them into another database.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_interop() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+    for line in [
+        "jobber -s 8:00 -e 10:30 -m first-job -t client,meeting",
+        "jobber -s 11:00 -e 11:45 -m second-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    for format in ["timewarrior", "timeclock"] {
+        let filename = std::env::temp_dir()
+            .join(format!("jobber-test-{format}-{}.data", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let mut output = Vec::new();
+        run_line_mut(
+            &mut output,
+            &format!("jobber -E --{format}"),
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+        std::fs::write(&filename, output).unwrap();
+
+        let mut imported = Jobs::new();
+        let operation = run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --import {filename} --{format}"),
+            &mut imported,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+        assert_eq!(
+            clean(&operation.to_string().into_bytes()),
+            format!("Imported 2 jobs from {filename} (added new tags  client ,  meeting )."),
+        );
+        for pos in 0..2 {
+            assert_eq!(imported[pos].start, jobs[pos].start);
+            assert_eq!(imported[pos].end, jobs[pos].end);
+            assert_eq!(imported[pos].message, jobs[pos].message);
+            assert_eq!(imported[pos].tags, jobs[pos].tags);
+        }
+
+        std::fs::remove_file(&filename).unwrap();
+    }
+}
+
+/// Import files which leave jobs open.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_import_open() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!("jobber-test-open-{}.data", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+
+    // two intervals without end
+    std::fs::write(
+        &filename,
+        "inc 20230201T080000Z # first\ninc 20230201T100000Z # second\n",
+    )
+    .unwrap();
+    let mut jobs = Jobs::new();
+    match run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --import {filename} --timewarrior"),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    ) {
+        Err(Error::Import(_, errors)) => assert_eq!(errors.len(), 1),
+        _ => panic!("expected import error"),
+    }
+    assert_eq!(jobs.count(), 0);
+
+    // trailing clock-in while a job is open
+    std::fs::write(&filename, "i 2023/02/01 08:00:00 first\n").unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 7:00",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --import {filename} --timeclock"),
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::OpenJob(_, _))
+    ));
+    assert_eq!(jobs.count(), 1);
+
+    std::fs::remove_file(&filename).unwrap();
+}
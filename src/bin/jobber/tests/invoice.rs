@@ -0,0 +1,149 @@
+//! Testing option `--invoice`.
+
+use super::clean;
+use crate::*;
+
+/// Create invoices and bill jobs.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_invoice() {
+    let context = Context::new_test("2023-2-3 12:00");
+
+    let mut jobs = Jobs::new();
+    run_args_mut(
+        &mut std::io::stdout(),
+        &[
+            "jobber",
+            "-t",
+            "acme",
+            "--rate",
+            "20",
+            "--tax",
+            "19",
+            "--client",
+            "ACME Inc.\\nMain Street 1",
+        ],
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    for line in [
+        "jobber -s 1.2.,8:00 -e 9:30 -t acme -m coding",
+        "jobber -s 1.2.,10:00 -e 10:30 -t acme -m meeting",
+        "jobber -s 2.2.,8:00 -e 9:00 -t acme -m coding",
+        "jobber -s 2.2.,11:00 -e 12:00 -t other -m other-client",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --invoice -t acme",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "# Invoice 1
+
+Date: 2023-02-03
+
+ACME Inc.  
+Main Street 1
+
+| Date | Description | Hours | Amount |
+|---|---|--:|--:|
+| 2023-02-01 | coding; meeting | 2 | $40.00 |
+| 2023-02-02 | coding | 1 | $20.00 |
+
+Hours: 3  
+Net: $60.00  
+Tax (19%): $11.40  
+**Total: $71.40**
+"
+    );
+
+    // billed jobs are marked and won't be billed again
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -l 1",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(clean(&output).contains("Invoice: 1"));
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --invoice -t acme",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::NothingToInvoice)
+    ));
+
+    // other client has no rate
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --invoice -t other",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::MissingRate(4))
+    ));
+
+    // undo makes jobs unbilled again and invoice number continues with configured one
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -t acme --invoice-number 42 --currency €",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --invoice 1.2. -t acme --group-by message --format html",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("<h1>Invoice 42</h1>"));
+    assert!(output.contains("<address>ACME Inc.<br>Main Street 1</address>"));
+    assert!(output.contains(
+        "<tr><td>meeting</td><td class=\"number\">0.5</td><td class=\"number\">€10.00</td></tr>"
+    ));
+    assert!(jobs[0].is_billed());
+    assert!(!jobs[2].is_billed());
+}
@@ -0,0 +1,89 @@
+//! Testing job IDs.
+
+use crate::*;
+
+/// Reference jobs by ID after their positions have changed.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_job_id() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    // add two jobs
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 8:00 -e 10:30 -m first-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 11:00 -e 12:30 -m second-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_ne!(jobs[0].id, jobs[1].id);
+    let id = jobs[1].id.clone();
+
+    // delete and purge first job so that the second one moves
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --delete 1",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --purge",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[0].id, id);
+
+    // edit by a prefix of the ID
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --edit @{} -m edited-job", &id.as_str()[..6]),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[0].message, Some("edited-job".into()));
+    assert_eq!(jobs[0].id, id);
+
+    // delete by ID
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --delete @{id}"),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 0);
+
+    // unknown ID
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --edit @0123456789abcdef0 -m unknown",
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::JobIdNotFound(_))
+    ));
+}
@@ -0,0 +1,107 @@
+//! Testing options `--lock` and `--unlock`.
+
+use crate::*;
+
+/// Lock jobs and refuse to edit or delete them until they get unlocked.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_lock() {
+    let context = Context::new_test("2023-4-3 12:00");
+    let mut jobs = Jobs::new();
+
+    // add jobs in march and april and an open one
+    for line in [
+        "jobber -s 30.3.,8:00 -e 10:30 -m march",
+        "jobber -s 1.4.,8:00 -e 10:30 -m april",
+        "jobber -s 3.4.,11:00 -m open",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    // lock everything up to 31.3.
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --lock ..31.3.",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(operation.to_string(), "Locked 1 job(s) at position(s): 1");
+    assert!(jobs[0].is_locked());
+    assert!(!jobs[1].is_locked());
+
+    // locked jobs can not be edited or deleted
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --edit 1 -m changed",
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::JobsLocked(positions)) if positions.to_string() == "1"
+    ));
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --delete 1-2",
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::JobsLocked(positions)) if positions.to_string() == "1"
+    ));
+    assert_eq!(jobs.count(), 3);
+
+    // open job does not get locked and still can be ended
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --lock",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -e 12:00",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert!(jobs[1].is_locked());
+    assert!(!jobs[2].is_locked());
+
+    // unlocked jobs can be edited again
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --unlock 1",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --edit 1 -m changed",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[0].message, Some("changed".into()));
+    assert!(jobs[1].is_locked());
+}
@@ -0,0 +1,123 @@
+//! Testing option `--format`.
+
+use crate::*;
+
+/// List and report jobs as Markdown and HTML.
+///
+/// - [x] check argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_markup() {
+    let context = Context::new_test("2023-2-1 12:00");
+
+    let mut jobs = Jobs::new();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --rate 20",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 1.2.,8:00 -e 9:30 -m fish&chips|sauce",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -l --format markdown",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("| Pos | ID | Start | End | Hours | Pay | Message | Tags |\n"));
+    assert!(output.contains("| 1.5 | $30 | fish&chips\\|sauce |  |\n"));
+    assert!(output.ends_with("\n**Total:** 1 job(s), 1.5 hours = $30\n"));
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -r --format markdown",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"## 2/2023
+
+| Day | Sun | Mon | Tue | Wed | Thu | Fri | Sat | Week |
+|--:|--:|--:|--:|--:|--:|--:|--:|--:|
+|  |  |  |  | 1.5 | - | - | - | 1.5 |
+| 5 | - | - | - | - | - | - | - | 0 |
+| 12 | - | - | - | - | - | - | - | 0 |
+| 19 | - | - | - | - | - | - | - | 0 |
+| 26 | - | - | - |  |  |  |  | 0 |
+
+Feb 2023: 1.5 hours = $30
+
+**Total:** 1 job(s), 1.5 hours = $30
+"#
+    );
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -l --format html",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("<!DOCTYPE html>\n"));
+    assert!(output.contains("<td>fish&amp;chips|sauce</td>"));
+    assert!(output.contains("Total: 1 job(s), 1.5 hours = $30"));
+    assert!(output.ends_with("</html>\n"));
+
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -r --format html",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("<h2>2/2023</h2>"));
+    assert!(output.contains("<td colspan=\"9\">Feb 2023: 1.5 hours = $30</td>"));
+
+    // configured currency is used for all amounts
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --currency €",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber -l --format markdown",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("| 1.5 | €30 | fish&chips\\|sauce |  |\n"));
+    assert!(output.ends_with("\n**Total:** 1 job(s), 1.5 hours = €30\n"));
+}
@@ -0,0 +1,176 @@
+//! Testing option `--merge`.
+
+use crate::*;
+
+/// Merge jobs and configuration of another database.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_merge() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!("jobber-test-merge-{}.json", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+
+    // our database with one job and a rate
+    let mut jobs = Jobs::new();
+    for line in ["jobber -s 8:00 -e 10:00 -m first-job", "jobber --rate 100"] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    // their database shares the first job and adds two more and another configuration
+    let mut theirs = jobs.clone();
+    for line in [
+        "jobber -s 11:00 -e 12:00 -m second-job",
+        "jobber -s 9:00 -e 9:30 -m overlapping-job -t meeting",
+        "jobber --rate 90",
+        "jobber --rate 80 -t meeting",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut theirs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+    theirs.save(&filename, 0).unwrap();
+
+    // overlapping job must be confirmed
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --merge {filename}"),
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::Warnings(warnings)) if warnings.len() == 1
+    ));
+    assert_eq!(jobs.count(), 1);
+
+    // merge
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --merge {filename}"),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 3);
+    assert_eq!(jobs.configuration.base.rate, Some(100.0));
+    assert_eq!(
+        jobs.configuration.tags.get("meeting").unwrap().rate,
+        Some(80.0)
+    );
+    assert_eq!(
+        operation.to_string(),
+        format!(
+            "Merged 2 job(s) from {filename} (skipped 1 existing job(s)).\n\
+             Configuration conflicts:\n  \
+             base: rate is 100 here but 90 in other database (kept 100)"
+        )
+    );
+
+    // merging again changes nothing
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber --merge {filename}"),
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 3);
+
+    std::fs::remove_file(&filename).unwrap();
+}
+
+/// Merging is refused for a second open job and checks merged jobs against each other.
+///
+/// - [ ] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_merge_checks() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!(
+            "jobber-test-merge-checks-{}.json",
+            std::process::id()
+        ))
+        .to_string_lossy()
+        .to_string();
+
+    // our database has an open job
+    let mut jobs = Jobs::new();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 11:00 -m our-open-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    // their database has two jobs overlapping each other (but none of ours) and an open job
+    let mut theirs = Jobs::new();
+    for line in [
+        "jobber -s 8:00 -e 9:00 -m first-job",
+        "jobber -s 8:30 -e 9:30 -m overlapping-job",
+        "jobber -s 10:00 -m their-open-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut theirs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+    theirs.save(&filename, 0).unwrap();
+
+    // a second open job is refused
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --merge {filename}"),
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::OpenJob(0, _))
+    ));
+    assert_eq!(jobs.count(), 1);
+
+    // without the open job the merged jobs overlap each other
+    let mut jobs = Jobs::new();
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber --merge {filename}"),
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::Warnings(warnings)) if warnings.len() == 1
+    ));
+    assert_eq!(jobs.count(), 0);
+
+    std::fs::remove_file(&filename).unwrap();
+}
@@ -1,10 +1,26 @@
 mod add;
 mod back;
+mod balance;
 mod delete;
 mod edit;
 mod export;
+mod filter;
+mod ics;
+mod import;
+mod interop;
+mod invoice;
+mod job_id;
+mod lock;
+mod markup;
+mod merge;
+mod output;
+mod purge;
 mod range;
+mod relative;
+mod search;
 mod start;
+mod undelete;
+mod undo;
 
 use regex::Regex;
 
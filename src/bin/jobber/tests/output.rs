@@ -0,0 +1,97 @@
+//! Testing option `--output`.
+
+use crate::*;
+
+/// Write export and list into files.
+///
+/// - [x] check argument parsing
+/// - [ ] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_output() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let filename = std::env::temp_dir()
+        .join(format!("jobber-test-output-{}.json", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    let _ = std::fs::remove_file(&filename);
+
+    let mut jobs = Jobs::new();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 8:00 -e 10:30 -m first-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    // format is chosen by file extension
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        &format!("jobber -E --output {filename}"),
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(output.is_empty());
+    let export: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&filename).unwrap()).unwrap();
+    assert_eq!(export["jobs"][0]["message"], "first-job");
+
+    // existing file needs confirmation
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber -l -o {filename}"),
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::OutputFileExists(_))
+    ));
+    run_line_mut(
+        &mut std::io::stdout(),
+        &format!("jobber -l -o {filename}"),
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    let listed = std::fs::read_to_string(&filename).unwrap();
+    assert!(listed.contains("Message: first-job"));
+    // no terminal styles within files
+    assert!(!listed.contains('\x1b'));
+
+    // failing operation keeps the file
+    for line in [
+        "jobber -s 11:00 -e 12:00 -m second-job -t a,b",
+        "jobber -t a --rate 10",
+        "jobber -t b --rate 20",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            &format!("jobber -E --json -o {filename}"),
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::TagCollision(_))
+    ));
+    assert_eq!(std::fs::read_to_string(&filename).unwrap(), listed);
+
+    std::fs::remove_file(&filename).unwrap();
+}
@@ -0,0 +1,98 @@
+//! Testing option `--purge`.
+
+use crate::*;
+
+/// Permanently remove deleted jobs.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_purge() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    // add four jobs
+    for line in [
+        "jobber -s 8:00 -e 9:00 -m first-job",
+        "jobber -s 9:00 -e 10:00 -m second-job",
+        "jobber -s 10:00 -e 11:00 -m third-job",
+        "jobber -s 11:00 -e 12:00 -m fourth-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    // delete second job
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --delete 2",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    // jobs deleted later than the given time will be kept
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --purge 1.2.2023,11:00",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(operation.to_string(), "There are no deleted jobs to purge.");
+    assert_eq!(jobs.iter().len(), 4);
+
+    // purge needs confirmation
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --purge",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::Warnings(_))
+    ));
+    assert_eq!(jobs.iter().len(), 4);
+
+    // purge deleted job
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --purge",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.iter().len(), 3);
+    assert_eq!(jobs.count(), 3);
+    assert_eq!(jobs[1].message, Some("third-job".into()));
+    assert_eq!(
+        operation.to_string(),
+        "Purged 1 deleted job(s) at position(s): 2\n\nRenumbered remaining jobs:\n\n    Pos: 3-4 -> 2-3\n"
+    );
+
+    // journal forgot the purged job but can undo adding the renumbered fourth one
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(operation, Operation::Undo(entries) if entries.len() == 1));
+    assert_eq!(jobs.iter().len(), 2);
+    assert_eq!(jobs[1].message, Some("third-job".into()));
+    assert_eq!(jobs.journal().undoable(), 2);
+}
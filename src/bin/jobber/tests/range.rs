@@ -25,13 +25,16 @@ fn test_range() {
         &context,
     );
 
-    assert!(matches!(run_line_mut(
-        &mut std::io::stdout(),
-        "jobber -r1.1.-",
-        &mut jobs,
-        Checks::all(),
-        &context,
-    ), Err(Error::RangeFormat(_))));
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber -r1.1.-",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::RangeFormat(_))
+    ));
 }
 
 fn add_job(jobs: &mut Jobs, line: &str, context: &Context) {
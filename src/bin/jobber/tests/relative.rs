@@ -0,0 +1,117 @@
+//! Testing relative and named dates and times.
+
+use crate::*;
+
+/// Add and list jobs by using time offsets and names of days.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_relative() {
+    // 2023-2-1 is a wednesday
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    for args in [
+        [
+            "jobber",
+            "-s",
+            "last wed,9:00",
+            "-e",
+            "17:00",
+            "-m",
+            "last week",
+        ],
+        [
+            "jobber",
+            "-s",
+            "yesterday,9:00",
+            "-e",
+            "17:00",
+            "-m",
+            "yesterday",
+        ],
+        ["jobber", "-s", "-1h15m", "-e", "+30m", "-m", "today"],
+    ] {
+        run_args_mut(
+            &mut std::io::stdout(),
+            &args,
+            &mut jobs,
+            Checks::all(),
+            &context,
+        )
+        .unwrap();
+    }
+    assert_eq!(jobs[0].start, "2023-1-25 9:00".into());
+    assert_eq!(jobs[1].end, Some("2023-1-31 17:00".into()));
+    assert_eq!(jobs[2].start, "2023-2-1 10:45".into());
+    assert_eq!(jobs[2].end, Some("2023-2-1 12:30".into()));
+
+    // start an open job and end it relatively
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s +1h -m later",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -e +2h",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[3].end, Some("2023-2-1 14:00".into()));
+
+    // offsets and durations in any duration format
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s -P1DT30M -d PT1H30M -m iso",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[4].start, "2023-1-31 11:30".into());
+    assert_eq!(jobs[4].end, Some("2023-1-31 13:00".into()));
+
+    // list jobs since monday until yesterday and since two days ago until now
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -l mon..yesterday",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(operation, Operation::List(positions, ..) if positions.len() == 2));
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -l -2d..",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(operation, Operation::List(positions, ..) if positions.len() == 3));
+
+    // relative ends with time are not extended to the next day
+    let operation = run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -l -3h..+1h",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(
+        operation,
+        Operation::List(_, range, ..)
+            if range == Range::TimeRange("2023-2-1 9:00".into(), "2023-2-1 13:00".into())
+    ));
+}
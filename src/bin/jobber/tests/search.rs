@@ -0,0 +1,83 @@
+//! Testing search within messages.
+
+use super::clean;
+use crate::*;
+
+/// Search messages by text and regular expression to list, export and delete jobs.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_search() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    for line in [
+        "jobber -s 1.1.,8:00 -e 9:00 -m fixed-the-login-bug",
+        "jobber -s 2.1.,8:00 -e 9:00 -m fixed-the-logout-bug",
+        "jobber -s 3.1.,8:00 -e 9:00 -m meeting",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        )
+        .unwrap();
+    }
+
+    // search text ignoring case and highlight matches
+    let mut output = Vec::new();
+    let operation = run_args_mut(
+        &mut output,
+        &["jobber", "-l", "\"LOGIN\""],
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(operation, Operation::List(positions, ..) if positions.to_string() == "1"));
+    #[cfg(not(feature = "colors"))]
+    assert!(clean(&output).contains("Message: fixed-the-[login]-bug\n"));
+    #[cfg(feature = "colors")]
+    assert!(clean(&output).contains("Message: fixed-the-login-bug\n"));
+
+    // search by regular expression within export
+    let operation = run_args_mut(
+        &mut std::io::stdout(),
+        &["jobber", "-E", "/log(in|out)/ & !1.1.", "--csv", "pos"],
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert!(
+        matches!(operation, Operation::ExportCSV(positions, ..) if positions.to_string() == "2")
+    );
+
+    // delete by search
+    run_args_mut(
+        &mut std::io::stdout(),
+        &["jobber", "--delete", "/-bug$/"],
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 1);
+
+    // invalid regular expression
+    assert!(matches!(
+        run_args_mut(
+            &mut std::io::stdout(),
+            &["jobber", "-r", "/log(in/"],
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::FilterFormat(..))
+    ));
+}
@@ -0,0 +1,97 @@
+//! Testing options `--deleted` and `--undelete`.
+
+use super::clean;
+use crate::*;
+
+/// List and restore deleted jobs.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [x] check output
+///
+#[test]
+fn test_undelete() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    // add two jobs
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 8:00 -e 10:30 -m first-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 11:00 -e 12:30 -m second-job",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+
+    // delete both jobs
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --delete 1-2",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 0);
+
+    // list deleted jobs
+    let mut output = Vec::new();
+    run_line_mut(
+        &mut output,
+        "jobber --deleted 1",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(
+        clean(&output),
+        format!(
+            r#"
+    Pos: 1
+     ID: {id}
+  Start: Wed Feb 01 2023, 08:00
+    End: Wed Feb 01 2023, 10:30
+  Hours: 2.5 ++-
+Message: first-job
+Deleted: Wed Feb 01 2023, 12:00
+
+"#,
+            id = jobs[0].id
+        )
+    );
+
+    // restore the second job
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undelete 2",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 1);
+    assert!(!jobs[1].is_deleted());
+    assert!(jobs[0].is_deleted());
+
+    // undeleted jobs can not be restored
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --undelete 2",
+            &mut jobs,
+            Checks::omit(),
+            &context,
+        ),
+        Err(Error::NothingToUndelete)
+    ));
+}
@@ -0,0 +1,202 @@
+//! Testing options `--undo` and `--redo`.
+
+use crate::*;
+
+/// Undo and redo several modifications of a database.
+///
+/// - [x] check argument parsing
+/// - [x] check database modification
+/// - [ ] check output
+///
+#[test]
+fn test_undo() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+
+    // nothing to undo in an empty database
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --undo",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::NothingToUndo)
+    ));
+
+    // add two jobs
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 8:00 -e 9:00 -m first-job",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber -s 10:00 -e 11:00 -m second-job",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+
+    // edit first job
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --edit 1 -m edited-job",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[0].message, Some("edited-job".into()));
+
+    // undo edit
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs[0].message, Some("first-job".into()));
+
+    // undo adding second job too
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo 1",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.iter().len(), 1);
+
+    // redo both
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --redo 2",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.iter().len(), 2);
+    assert_eq!(jobs[0].message, Some("edited-job".into()));
+
+    // nothing left to redo
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --redo",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::NothingToRedo)
+    ));
+
+    // undo deletion
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --delete 1-2",
+        &mut jobs,
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 0);
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.count(), 2);
+
+    // new modifications drop undone ones
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --rate 100",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --redo",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::NothingToRedo)
+    ));
+
+    // undo configuration change
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert_eq!(jobs.configuration.base.rate, None);
+
+    // undoing no operation is refused
+    assert!(matches!(
+        run_line_mut(
+            &mut std::io::stdout(),
+            "jobber --undo 0",
+            &mut jobs,
+            Checks::all(),
+            &context,
+        ),
+        Err(Error::NothingToUndo)
+    ));
+
+    // journal keeps no more than the configured number of operations
+    jobs.set_journal_limit(2);
+    for line in [
+        "jobber -s 12:00 -e 13:00 -m third-job",
+        "jobber -s 13:00 -e 14:00 -m fourth-job",
+    ] {
+        run_line_mut(
+            &mut std::io::stdout(),
+            line,
+            &mut jobs,
+            Checks::all(),
+            &context,
+        )
+        .unwrap();
+    }
+    assert_eq!(jobs.journal().undoable(), 2);
+
+    // locked jobs get unlocked by undoing the lock before any other change of them is reverted
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --lock",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(jobs[0].is_locked());
+    run_line_mut(
+        &mut std::io::stdout(),
+        "jobber --undo",
+        &mut jobs,
+        Checks::all(),
+        &context,
+    )
+    .unwrap();
+    assert!(!jobs[0].is_locked());
+}
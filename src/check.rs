@@ -15,6 +15,12 @@ pub enum Check {
     /// Emit `Warning::ConfirmDeletion` if a job is about to be deleted.
     /// This check is done outside of `Checks`.
     ConfirmDeletion,
+    /// Emit `Warning::ConfirmPurge` if deleted jobs are about to be removed permanently.
+    /// This check is done outside of `Checks`.
+    ConfirmPurge,
+    /// Emit `Error::OutputFileExists` if an output file is about to be overwritten.
+    /// This check is done outside of `Checks`.
+    OutputFileExists,
 }
 
 /// A set of selectable checks.
@@ -80,8 +86,8 @@ impl Checks {
             }
             if !overlapping.is_empty() {
                 warnings.push(Warning::Overlaps {
-                    new: job.clone(),
-                    existing: overlapping.into(),
+                    new: Box::new(job.clone()),
+                    existing: Box::new(overlapping.into()),
                 });
             }
         }
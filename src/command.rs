@@ -53,17 +53,52 @@ pub enum Command {
     List {
         range: Range,
         tags: Option<TagSet>,
+        format: OutputFormat,
     },
     /// Report jobs
     Report {
         range: Range,
         tags: Option<TagSet>,
+        format: OutputFormat,
+    },
+    /// Report hours compared to target hours
+    Balance {
+        range: Range,
+        tags: Option<TagSet>,
     },
     /// Report jobs as CSV
     ExportCSV {
         range: Range,
         tags: Option<TagSet>,
         columns: String,
+        dialect: CsvDialect,
+    },
+    /// Report jobs as JSON
+    ExportJSON {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Report jobs as iCalendar
+    ExportICS {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Report jobs as Timewarrior data
+    ExportTimewarrior {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Report jobs as timeclock
+    ExportTimeclock {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Create invoice about the unbilled jobs of a client
+    Invoice {
+        range: Range,
+        tags: TagSet,
+        grouping: Grouping,
+        format: OutputFormat,
     },
     /// Display whole configuration
     ShowConfiguration,
@@ -72,8 +107,13 @@ pub enum Command {
         tags: Option<TagSet>,
         update: Properties,
     },
-    /// Import CSV database of legacy Ruby *jobber* version
-    LegacyImport {
+    /// Import jobs from a file
+    Import {
+        filename: String,
+        format: ImportFormat,
+    },
+    /// Merge another *jobber* database
+    Merge {
         filename: String,
     },
     /// List all known tags
@@ -83,7 +123,7 @@ pub enum Command {
     },
     /// Edit an existing job.
     Edit {
-        pos: Option<usize>,
+        pos: Option<JobRef>,
         start: Option<DateTime>,
         end: EndOrDuration,
         message: Option<Option<String>>,
@@ -94,6 +134,43 @@ pub enum Command {
         range: Range,
         tags: Option<TagSet>,
     },
+    /// List deleted jobs
+    ListDeleted {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Restore deleted jobs.
+    Undelete {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Protect jobs from modification and deletion.
+    Lock {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Remove protection from jobs.
+    Unlock {
+        range: Range,
+        tags: Option<TagSet>,
+    },
+    /// Permanently remove jobs which were deleted before the given time (or all deleted jobs if `None`).
+    Purge {
+        before: Option<DateTime>,
+    },
+    /// Write output of another command into a file.
+    Output {
+        filename: String,
+        command: Box<Command>,
+    },
+    /// Undo the last `count` operations which modified the database.
+    Undo {
+        count: usize,
+    },
+    /// Redo the last `count` undone operations.
+    Redo {
+        count: usize,
+    },
 }
 
 impl Command {
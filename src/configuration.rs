@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Configuration of a *jobber* database.
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Configuration {
     /// Configuration used when no tag related configuration fit
     pub base: Properties,
@@ -67,6 +67,9 @@ impl Configuration {
     }
 }
 
+/// Currency sign if none is configured.
+pub const DEFAULT_CURRENCY: char = '$';
+
 /// Properties within the database configuration.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Properties {
@@ -76,6 +79,16 @@ pub struct Properties {
     pub rate: Option<f64>,
     /// Maximum work hours per day
     pub max_hours: Option<u32>,
+    /// Client address printed on invoices
+    pub client: Option<String>,
+    /// Tax rate in percent added to invoices
+    pub tax: Option<f64>,
+    /// Currency sign printed on invoices
+    pub currency: Option<char>,
+    /// Lowest number of the next invoice
+    pub invoice: Option<u32>,
+    /// Contracted work hours per weekday
+    pub target: Option<Target>,
 }
 
 impl Properties {
@@ -98,6 +111,26 @@ impl Properties {
             self.max_hours = Some(max_hours);
             modified = true;
         }
+        if let Some(client) = properties.client {
+            self.client = Some(client);
+            modified = true;
+        }
+        if let Some(tax) = properties.tax {
+            self.tax = Some(tax);
+            modified = true;
+        }
+        if let Some(currency) = properties.currency {
+            self.currency = Some(currency);
+            modified = true;
+        }
+        if let Some(invoice) = properties.invoice {
+            self.invoice = Some(invoice);
+            modified = true;
+        }
+        if let Some(target) = properties.target {
+            self.target = Some(target);
+            modified = true;
+        }
         modified
     }
 }
@@ -108,6 +141,11 @@ impl Default for Properties {
             resolution: Some(0.25),
             rate: None,
             max_hours: None,
+            client: None,
+            tax: None,
+            currency: None,
+            invoice: None,
+            target: None,
         }
     }
 }
@@ -123,6 +161,79 @@ impl std::fmt::Display for Properties {
         if let Some(max_hours) = self.max_hours {
             writeln!(f, "Maximum work time: {} hours", max_hours)?
         };
+        if let Some(client) = &self.client {
+            writeln!(f, "Client: {}", client.replace('\n', ", "))?
+        };
+        if let Some(tax) = self.tax {
+            writeln!(f, "Tax rate: {}%", tax)?
+        };
+        if let Some(currency) = self.currency {
+            writeln!(f, "Currency: {}", currency)?
+        };
+        if let Some(invoice) = self.invoice {
+            writeln!(f, "Next invoice number: {}", invoice)?
+        };
+        if let Some(target) = &self.target {
+            writeln!(f, "Target hours: {}", target)?
+        };
         Ok(())
     }
 }
+
+/// Names of the weekdays in order of [Target].
+const TARGET_WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Contracted work hours per weekday (starting with monday).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Target(pub [f64; 7]);
+
+impl Target {
+    /// Parse comma separated hours per weekday like `8,8,8,8,6` (missing weekdays get no hours).
+    pub fn parse(target: &str) -> Result<Self, Error> {
+        let mut hours = [0.0; 7];
+        let values: Vec<&str> = target.split(',').collect();
+        if values.len() > hours.len() {
+            return Err(Error::TargetFormat(target.into()));
+        }
+        for (n, value) in values.iter().enumerate() {
+            hours[n] = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|hours| (0.0..=24.0).contains(hours))
+                .ok_or_else(|| Error::TargetFormat(target.into()))?;
+        }
+        Ok(Self(hours))
+    }
+    /// Get target hours at the given weekday.
+    pub fn hours(&self, weekday: chrono::Weekday) -> f64 {
+        self.0[weekday.num_days_from_monday() as usize]
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (n, (weekday, hours)) in TARGET_WEEKDAYS.iter().zip(self.0).enumerate() {
+            if n > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{weekday} {hours}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Test parsing target hours.
+#[test]
+fn test_target() {
+    let target = Target::parse("8,8,8,8,6").unwrap();
+    assert_eq!(target.hours(chrono::Weekday::Fri), 6.0);
+    assert_eq!(target.hours(chrono::Weekday::Sun), 0.0);
+    assert_eq!(
+        target.to_string(),
+        "Mon 8, Tue 8, Wed 8, Thu 8, Fri 6, Sat 0, Sun 0"
+    );
+    assert!(Target::parse("8,8,8,8,8,8,8,8").is_err());
+    assert!(Target::parse("8,x").is_err());
+    assert!(Target::parse("25").is_err());
+}
@@ -51,17 +51,17 @@ impl DateTime {
         let local = Local.from_local_datetime(local).unwrap();
         Self(chrono::DateTime::from(local))
     }
+    /// Convert from naive local date and time or return `None` if it does not exist
+    /// (e.g. when skipped by daylight saving time).
+    pub fn try_from_local(local: &NaiveDateTime) -> Option<Self> {
+        Local
+            .from_local_datetime(local)
+            .earliest()
+            .map(|local| Self(chrono::DateTime::from(local)))
+    }
     /// Convert from naive local date and time string.
     fn from_local_str(local: &str) -> Self {
-        Self(
-            Utc.from_local_datetime(
-                &Local
-                    .datetime_from_str(local, "%Y-%m-%d %H:%M")
-                    .unwrap()
-                    .naive_utc(),
-            )
-            .unwrap(),
-        )
+        Self::from_local(&NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").unwrap())
     }
     /// Convert from naive RFC3339 date and time.
     pub fn from_rfc3339(rfc3339: &str) -> Result<Self, Error> {
@@ -71,6 +71,14 @@ impl DateTime {
                 .into(),
         ))
     }
+    /// Convert into RFC3339 date and time.
+    pub fn to_rfc3339(&self) -> String {
+        self.0.to_rfc3339()
+    }
+    /// Format in UTC with the given format string (see `format::strftime()` for available formats)
+    pub fn format_utc(&self, format: &str) -> String {
+        self.0.format(format).to_string()
+    }
     /// Format with the given format string (see `format::strftime()` for available formats)
     pub fn format(&self, format: &str) -> String {
         self.into_local().format(format).to_string()
@@ -185,6 +193,12 @@ impl Date {
                 .unwrap(),
         )
     }
+    pub fn midnight(&self) -> DateTime {
+        DateTime::from_local(&self.0.and_hms_opt(0, 0, 0).unwrap())
+    }
+    pub fn next_midnight(&self) -> DateTime {
+        DateTime::from_local(&self.0.succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap())
+    }
     pub fn first_day_of_previous_month(&self) -> DateTime {
         DateTime::from_local(
             &chrono::NaiveDate::from_ymd_opt(self.0.year(), self.0.month() - 1, 1)
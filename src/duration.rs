@@ -3,12 +3,16 @@
 use crate::prelude::*;
 use regex::Regex;
 
+/// Largest amount of minutes (about 10,000 years) a parsed duration may have so that adding it to a date
+/// and time can not overflow.
+const MAX_MINUTES: i64 = 10_000 * 366 * 24 * 60;
+
 /// Duration in time.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Duration {
     /// In no time at all.
     Zero,
-    /// Hours and minutes.
+    /// Hours and minutes (both negative if duration is negative).
     HM { hours: i64, minutes: i64 },
 }
 
@@ -20,13 +24,25 @@ impl Duration {
             minutes: 0,
         }
     }
-    /// Parse duration from a string.
+    /// Create duration with the given amount of minutes.
+    pub fn minutes(minutes: i64) -> Self {
+        Self::HM {
+            hours: minutes / 60,
+            minutes: minutes % 60,
+        }
+    }
+    /// Parse duration from a string which may start with `-` to be negative.
     pub fn parse(duration: String) -> Result<Self, Error> {
-        match Self::parse_hm(&duration)
-            .or(Self::parse_hours(&duration).or(Self::parse_hm2(&duration)))
+        let (negative, unsigned) = match duration.trim().strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, duration.trim()),
+        };
+        match Self::parse_hm(unsigned).or(Self::parse_hours(unsigned)
+            .or(Self::parse_units(unsigned).or(Self::parse_iso(unsigned))))
         {
             Duration::Zero => Err(Error::DurationFormat(duration)),
-            duration => Ok(duration),
+            parsed if negative => Ok(Self::minutes(-parsed.num_minutes())),
+            parsed => Ok(parsed),
         }
     }
     /// Set duration in this instance if none was set before.
@@ -40,64 +56,76 @@ impl Duration {
     fn parse_hm(duration: &str) -> Self {
         let re = Regex::new(r"^(\d+):(\d{1,2})$").unwrap();
         if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: cap[1].parse::<i64>().unwrap(),
-                minutes: cap[2].parse::<i64>().unwrap(),
-            };
+            return Self::bounded(
+                cap[1]
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|hours| hours.checked_mul(60))
+                    .and_then(|minutes| minutes.checked_add(cap[2].parse::<i64>().unwrap())),
+            );
         }
         Self::Zero
     }
-    /// Parse duration from fractional hours string.
+    /// Parse duration from (fractional) hours string like `2`, `1.5`, `1,25` or `.5`.
     fn parse_hours(duration: &str) -> Self {
-        let re = Regex::new(r"^(\d+)[,.](\d{1,2})$").unwrap();
-        if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: cap[1].parse::<i64>().unwrap(),
-                minutes: (format!(".{}", &cap[2]).parse::<f64>().unwrap() * 60f64)
-                    as i64,
-            };
-        }
-        let re = Regex::new(r"^[,.](\d{1,2})$").unwrap();
-        if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: 0,
-                minutes: (format!(".{}", &cap[1]).parse::<f64>().unwrap() * 60f64)
-                    as i64,
-            };
+        let re = Regex::new(r"^(\d+|\d*[,.]\d+)$").unwrap();
+        if re.is_match(duration) {
+            return Self::bounded(Self::fraction(duration, 60.0));
         }
-        let re = Regex::new(r"^(\d{1,2})$").unwrap();
+        Self::Zero
+    }
+    /// Parse duration with units like `1d`, `2h`, `1.5h`, `90m` or `1h30m`.
+    fn parse_units(duration: &str) -> Self {
+        let re =
+            Regex::new(r"(?i)^(?:(\d+(?:[,.]\d+)?)d)?(?:(\d+(?:[,.]\d+)?)h)?(?:(\d+)m)?$").unwrap();
         if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: cap[1].parse::<u32>().unwrap() as i64,
-                minutes: 0,
-            };
+            return Self::from_captures(&cap, &[1440.0, 60.0, 1.0]);
         }
         Self::Zero
     }
-    /// Parse alternative Duration with `h` and `m`.
-    fn parse_hm2(duration: &str) -> Self {
-        let re = Regex::new(r"^(\d{1,2})h(\d{1,2})m$").unwrap();
+    /// Parse ISO 8601 duration like `PT1H30M` or `P1DT2H` (seconds get rounded to minutes).
+    fn parse_iso(duration: &str) -> Self {
+        let re = Regex::new(
+            r"(?i)^P(?:(\d+(?:[,.]\d+)?)D)?(?:T(?:(\d+(?:[,.]\d+)?)H)?(?:(\d+(?:[,.]\d+)?)M)?(?:(\d+(?:[,.]\d+)?)S)?)?$",
+        )
+        .unwrap();
         if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: cap[1].parse::<u32>().unwrap() as i64,
-                minutes: cap[2].parse::<u32>().unwrap() as i64,
-            };
+            if !duration.to_uppercase().ends_with('T') {
+                return Self::from_captures(&cap, &[1440.0, 60.0, 1.0, 1.0 / 60.0]);
+            }
         }
-        let re = Regex::new(r"^(\d{1,2})m$").unwrap();
-        if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: 0,
-                minutes: cap[1].parse::<u32>().unwrap() as i64,
-            };
+        Self::Zero
+    }
+    /// Sum up the captured numbers multiplied by their amount of minutes per unit.
+    ///
+    /// Returns `Zero` if nothing was captured or if the sum is too large.
+    fn from_captures(cap: &regex::Captures, minutes_per_unit: &[f64]) -> Self {
+        let mut minutes = None;
+        for (n, factor) in minutes_per_unit.iter().enumerate() {
+            if let Some(number) = cap.get(n + 1) {
+                match Self::fraction(number.as_str(), *factor)
+                    .and_then(|fraction| fraction.checked_add(minutes.unwrap_or(0)))
+                {
+                    Some(sum) => minutes = Some(sum),
+                    None => return Self::Zero,
+                }
+            }
         }
-        let re = Regex::new(r"^(\d{1,2})h$").unwrap();
-        if let Some(cap) = re.captures_iter(duration).next() {
-            return Self::HM {
-                hours: cap[1].parse::<u32>().unwrap() as i64,
-                minutes: 0,
-            };
+        minutes.map_or(Self::Zero, |minutes| Self::bounded(Some(minutes)))
+    }
+    /// Convert a number with optional decimal comma or point into rounded minutes.
+    ///
+    /// Returns `None` if the result exceeds [MAX_MINUTES].
+    fn fraction(number: &str, minutes_per_unit: f64) -> Option<i64> {
+        let minutes = (number.replace(',', ".").parse::<f64>().unwrap() * minutes_per_unit).round();
+        (minutes <= MAX_MINUTES as f64).then_some(minutes as i64)
+    }
+    /// Create duration from `minutes` or return `Zero` if there are none or more than [MAX_MINUTES].
+    fn bounded(minutes: Option<i64>) -> Self {
+        match minutes {
+            Some(minutes) if minutes <= MAX_MINUTES => Self::minutes(minutes),
+            _ => Self::Zero,
         }
-        Self::Zero
     }
     /// Return duration in minutes.
     pub fn num_minutes(&self) -> i64 {
@@ -107,6 +135,13 @@ impl Duration {
         }
     }
 }
+
+impl PartialEq for Duration {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_minutes() == other.num_minutes()
+    }
+}
+
 impl From<Duration> for chrono::Duration {
     fn from(val: Duration) -> Self {
         match val {
@@ -187,3 +222,50 @@ fn test_duration() {
         }
     );
 }
+
+/// Test flexible, ISO 8601 and negative duration formats.
+#[test]
+fn test_duration_formats() {
+    let minutes = |duration: &str| Duration::parse(duration.to_string()).unwrap().num_minutes();
+    assert_eq!(minutes("1h30m"), 90);
+    assert_eq!(minutes("90m"), 90);
+    assert_eq!(minutes("120m"), 120);
+    assert_eq!(minutes("1.5h"), 90);
+    assert_eq!(minutes("1,25H"), 75);
+    assert_eq!(minutes("1d"), 1440);
+    assert_eq!(minutes("1d2h"), 1560);
+    assert_eq!(minutes("PT1H30M"), 90);
+    assert_eq!(minutes("pt45m"), 45);
+    assert_eq!(minutes("P1DT1H"), 1500);
+    assert_eq!(minutes("PT90S"), 2);
+    assert_eq!(minutes("1.333"), 80);
+    assert_eq!(minutes("100"), 6000);
+    assert_eq!(minutes("-1h30m"), -90);
+    assert_eq!(minutes("-0:15"), -15);
+    assert_eq!(minutes("-PT2H"), -120);
+
+    for duration in [
+        "",
+        "-",
+        "h",
+        "1x",
+        "1m30h",
+        "P",
+        "PT",
+        "1.h",
+        "--1h",
+        "9999999999999999d",
+        "99999999999d",
+        "99999999999999999999:00",
+        "-99999999999999999999",
+        "P99999999999DT1H",
+    ] {
+        assert!(Duration::parse(duration.to_string()).is_err(), "{duration}");
+    }
+
+    // display can be parsed back into the same duration
+    for minutes in -3000..3000 {
+        let duration = Duration::minutes(minutes);
+        assert_eq!(Duration::parse(duration.to_string()).unwrap(), duration);
+    }
+}
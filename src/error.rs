@@ -26,7 +26,7 @@ pub enum Error {
     Json(serde_json::Error),
     /// There still is an open job.
     #[error("There still is an open job:\n\n    Pos: {0}\n{1}")]
-    OpenJob(usize, Job),
+    OpenJob(usize, Box<Job>),
     /// There is no open job.
     #[error("There is no open job")]
     NoOpenJob,
@@ -66,12 +66,72 @@ pub enum Error {
     /// Parsing of a range failed
     #[error("Parsing of range '{0}' failed")]
     RangeFormat(String),
+    /// Parsing of a filter expression failed
+    #[error("Parsing of filter expression '{0}' failed: {1}")]
+    FilterFormat(String, String),
     /// Parsing of a duration failed
     #[error("Parsing of duration '{0}' failed")]
     DurationFormat(String),
     /// Parsing of a partial date and time failed
     #[error("Parsing of partial date and time '{0}' failed")]
     PartialDateTimeFormat(String),
+    /// Database is locked by another process
+    #[error("Database '{0}' is locked by another jobber process (use --wait to wait until it is released)")]
+    DatabaseBusy(String),
+    /// Database was written by a newer version of jobber
+    #[error("Database has schema version {0} (written by jobber {1}) which is newer than this version of jobber supports (schema version {}). Please update jobber.", schema::SCHEMA)]
+    SchemaTooNew(u32, String),
+    /// Database has an invalid schema
+    #[error("Database has invalid schema: {0}")]
+    SchemaFormat(String),
+    /// Storage backend is not supported
+    #[error("Database file '{0}' needs SQLite storage which is not supported by this build of jobber (compile with feature 'sqlite')")]
+    StorageUnsupported(String),
+    /// SQLite error
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(rusqlite::Error),
+    /// Parsing of a job reference failed
+    #[error("Parsing of job reference '{0}' failed (use a position or an ID like '@3f2a')")]
+    JobRefFormat(String),
+    /// No job found with ID
+    #[error("No job found with ID '@{0}'")]
+    JobIdNotFound(String),
+    /// More than one job found with ID
+    #[error("ID '@{0}' is ambiguous because it matches {1} jobs (use more digits)")]
+    AmbiguousJobId(String, usize),
+    /// Import of a file failed
+    #[error("Import of '{0}' failed:\n  {}", .1.join("\n  "))]
+    Import(String, Vec<String>),
+    /// Parsing of a time zone failed
+    #[error("Parsing of time zone '{0}' failed (use 'local', 'utc' or an offset like '+02:00')")]
+    TimeZoneFormat(String),
+    /// There are no jobs to invoice
+    #[error("There are no finished jobs to invoice which have not been billed yet")]
+    NothingToInvoice,
+    /// No rate to invoice a job
+    #[error(
+        "Can not invoice job at position {0} because no rate is configured for it (use --rate)"
+    )]
+    MissingRate(usize),
+    /// Parsing of target hours failed
+    #[error("Parsing of target hours '{0}' failed (use up to seven comma separated hours per weekday starting with monday like '8,8,8,8,6')")]
+    TargetFormat(String),
+    /// No target hours configured
+    #[error("No target hours configured (use --target)")]
+    MissingTarget,
+    /// Jobs are locked
+    #[error("Job(s) at position(s) {0} are locked (use --unlock to unlock them)")]
+    JobsLocked(Positions),
+    /// There is nothing to undo
+    #[error("There is nothing to undo")]
+    NothingToUndo,
+    /// There is nothing to redo
+    #[error("There is nothing to redo")]
+    NothingToRedo,
+    /// There are no deleted jobs to restore
+    #[error("There are no deleted jobs to restore")]
+    NothingToUndelete,
 }
 
 impl From<std::io::Error> for Error {
@@ -80,6 +140,13 @@ impl From<std::io::Error> for Error {
     }
 }
 
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
 impl From<std::fmt::Error> for Error {
     fn from(err: std::fmt::Error) -> Self {
         Error::Fmt(err)
@@ -91,7 +158,10 @@ impl From<std::fmt::Error> for Error {
 pub enum Warning {
     /// The job you want to add overlaps existing one(s)
     #[error("The job you want to add overlaps existing one(s):\n\nJob you want to add:\n\n{new}\nExisting overlapping jobs:\n\n{existing}")]
-    Overlaps { new: Job, existing: JobListOwned },
+    Overlaps {
+        new: Box<Job>,
+        existing: Box<JobListOwned>,
+    },
     #[error(
         "You have used some tags ({0}) which are unknown so far. Continue if you want to create them."
     )]
@@ -99,6 +169,9 @@ pub enum Warning {
     /// You are about to delete job(s) at the following position(s).
     #[error("You are about to delete job(s) at the following position(s): {0}")]
     ConfirmDeletion(Positions),
+    /// You are about to permanently remove deleted job(s) at the following position(s).
+    #[error("You are about to permanently remove deleted job(s) at the following position(s): {0}\nThis will renumber the remaining jobs and remove them from the undo journal.")]
+    ConfirmPurge(Positions),
 }
 
 /// List of jobs with index extracted from database list.
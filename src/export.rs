@@ -1,12 +1,14 @@
-//! CSV Export
+//! CSV and JSON Export
 
 use super::prelude::*;
 use itertools::Itertools;
+use serde::Serialize;
 
 /// Available export columns.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Column {
     Pos,
+    Id,
     Start,
     End,
     Duration,
@@ -23,7 +25,8 @@ impl Column {
     // Create column from String.
     pub fn from(column: &str) -> Result<Self, Error> {
         Ok(match column.to_lowercase().as_str() {
-            "#" | "pos" => Column::Pos,
+            "#" | "pos" | "position" => Column::Pos,
+            "id" => Column::Id,
             "s" | "start" => Column::Start,
             "e" | "end" => Column::End,
             "d" | "duration" => Column::Duration,
@@ -32,6 +35,7 @@ impl Column {
             "t" | "tags" => Column::Tags,
             "p" | "pay" => Column::Pay,
             "rate" => Column::Rate,
+            "max_hours" | "max.hours" => Column::MaxHours,
             "resolution" => Column::Resolution,
             _ => return Err(Error::UnknownColumn(column.to_string())),
         })
@@ -45,6 +49,7 @@ impl std::fmt::Display for Column {
             "{}",
             match self {
                 Column::Pos => "Position",
+                Column::Id => "ID",
                 Column::Start => "Start",
                 Column::End => "End",
                 Column::Duration => "Duration",
@@ -61,10 +66,19 @@ impl std::fmt::Display for Column {
 }
 
 /// List of columns to export.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Columns(Vec<Column>);
 
 impl Columns {
+    /// Parse comma separated list of column names.
+    pub fn parse(columns: &str) -> Result<Self, Error> {
+        Ok(Columns(
+            columns
+                .split(',')
+                .map(Column::from)
+                .collect::<Result<Vec<Column>, Error>>()?,
+        ))
+    }
     pub fn iter(&self) -> core::slice::Iter<'_, Column> {
         self.0.iter()
     }
@@ -89,80 +103,206 @@ impl From<String> for Columns {
 /// * `w`: Where the output goes
 /// * `jobs`: Jobs to export
 /// * `columns`: Column names
+/// * `dialect`: How to write the CSV
 pub fn export_csv<W: std::io::Write>(
     w: &mut W,
     jobs: &JobList,
     columns: &Columns,
+    dialect: &CsvDialect,
     context: &Context,
 ) -> Result<(), Error> {
-    let title = columns
-        .0
-        .iter()
-        .map(|c| format!(r#""{}""#, c))
-        .collect::<Vec<String>>()
-        .join(",");
-    writeln!(w, "{}", title)?;
+    if dialect.header {
+        let title: Vec<String> = columns
+            .iter()
+            .map(|column| quote(&column.to_string(), true, dialect))
+            .collect();
+        writeln!(w, "{}", title.join(&dialect.delimiter.to_string()))?;
+    }
+    let number = |number: f64| {
+        number
+            .to_string()
+            .replace('.', &dialect.decimal_separator.to_string())
+    };
     for (pos, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
-        for (c, column) in columns.iter().enumerate() {
-            if c > 0 {
-                write!(w, ",")?;
-            }
-            let properties = jobs.configuration.get_checked(&job.tags)?;
-            match column {
-                Column::Pos => write!(w, "{}", pos + 1)?,
-                Column::Start => write!(w, r#""{}""#, job.start.format("%m/%d/%Y %H:%M"))?,
-                Column::End => write!(
-                    w,
-                    r#""{}""#,
-                    if let Some(end) = job.end {
-                        end.format("%m/%d/%Y %H:%M")
-                    } else {
-                        context.time().format("%m/%d/%Y %H:%M")
-                    }
-                )?,
-                Column::Duration => write!(
-                    w,
-                    r#""{}""#,
-                    if let Some(end) = job.end {
-                        &end - &job.start
-                    } else {
-                        &context.time() - &job.start
-                    }
-                )?,
-                Column::Message => write!(
-                    w,
-                    r#""{}""#,
-                    str::replace(
-                        job.message.as_ref().unwrap_or(&"".to_string()),
-                        "\"",
-                        "\"\""
-                    )
-                )?,
-                Column::Hours => write!(w, "{}", job.hours(properties))?,
-                Column::Tags => write!(w, r#""{}""#, job.tags.0.join(","))?,
-                Column::Pay => {
-                    if let Some(rate) = properties.rate {
-                        write!(w, "{}", job.hours(properties) * rate)?;
-                    }
-                }
-                Column::Rate => {
-                    if let Some(rate) = jobs.get_configuration(&job.tags).rate {
-                        write!(w, "{rate}")?;
-                    }
-                }
-                Column::MaxHours => {
-                    if let Some(max_hours) = jobs.get_configuration(&job.tags).max_hours {
-                        write!(w, "{max_hours}",)?;
-                    }
-                }
-                Column::Resolution => {
-                    if let Some(resolution) = jobs.get_configuration(&job.tags).resolution {
-                        write!(w, "{resolution}",)?;
-                    }
-                }
-            }
+        let properties = jobs.configuration.get_checked(&job.tags)?;
+        let end = job.end.unwrap_or_else(|| context.time());
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| match column {
+                Column::Pos => quote(&(pos + 1).to_string(), false, dialect),
+                Column::Id => quote(job.id.as_str(), true, dialect),
+                Column::Start => quote(&job.start.format(&dialect.date_format), true, dialect),
+                Column::End => quote(&end.format(&dialect.date_format), true, dialect),
+                Column::Duration => quote(&(&end - &job.start).to_string(), true, dialect),
+                Column::Message => quote(job.message.as_deref().unwrap_or_default(), true, dialect),
+                Column::Hours => quote(&number(job.hours(properties)), false, dialect),
+                Column::Tags => quote(&job.tags.0.join(","), true, dialect),
+                Column::Pay => properties.rate.map_or(String::new(), |rate| {
+                    quote(&number(job.hours(properties) * rate), false, dialect)
+                }),
+                Column::Rate => jobs
+                    .get_configuration(&job.tags)
+                    .rate
+                    .map_or(String::new(), |rate| quote(&number(rate), false, dialect)),
+                Column::MaxHours => jobs
+                    .get_configuration(&job.tags)
+                    .max_hours
+                    .map_or(String::new(), |max_hours| {
+                        quote(&max_hours.to_string(), false, dialect)
+                    }),
+                Column::Resolution => jobs
+                    .get_configuration(&job.tags)
+                    .resolution
+                    .map_or(String::new(), |resolution| {
+                        quote(&number(resolution), false, dialect)
+                    }),
+            })
+            .collect();
+        writeln!(w, "{}", fields.join(&dialect.delimiter.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Put a field into quotes (if `dialect` wants it or if it's necessary) and double contained quotes.
+/// * `text`: `true` if field is text (not a number)
+fn quote(field: &str, text: bool, dialect: &CsvDialect) -> String {
+    let necessary = field.contains([dialect.delimiter, '"', '\n', '\r']);
+    if necessary
+        || match dialect.quoting {
+            Quoting::All => true,
+            Quoting::Strings => text,
+            Quoting::Necessary => false,
+        }
+    {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Version of the JSON export schema (increased on incompatible changes only).
+pub const JSON_SCHEMA: u32 = 1;
+
+/// Root object of the JSON export.
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    /// Version of the JSON export schema.
+    schema: u32,
+    /// Time of the export (RFC3339).
+    exported: String,
+    /// Whole database configuration.
+    configuration: &'a Configuration,
+    /// Exported jobs.
+    jobs: Vec<JsonJob<'a>>,
+    /// Sums over all exported jobs.
+    total: JsonTotal,
+}
+
+/// Job within the JSON export.
+#[derive(Serialize)]
+struct JsonJob<'a> {
+    /// Position (starting at `1`).
+    pos: usize,
+    id: &'a JobId,
+    /// Start time (RFC3339).
+    start: String,
+    /// End time (RFC3339) or `null` if job is still open.
+    end: Option<String>,
+    message: &'a Option<String>,
+    tags: &'a Vec<String>,
+    /// Number of the invoice or `null` if job is not billed.
+    invoice: Option<u32>,
+    /// `true` if job is locked.
+    locked: bool,
+    /// Hours considering resolution.
+    hours: f64,
+    /// Hours multiplied by rate or `null` if no rate is configured.
+    pay: Option<f64>,
+    /// Properties which were effective for this job.
+    properties: JsonProperties<'a>,
+}
+
+/// Effective properties of a job within the JSON export.
+///
+/// Fields are listed explicitly so that the schema does not change with [Properties].
+#[derive(Serialize)]
+struct JsonProperties<'a> {
+    /// Tag which configuration was used or `null` if base configuration was used.
+    tag: Option<String>,
+    resolution: Option<f64>,
+    rate: Option<f64>,
+    max_hours: Option<u32>,
+    client: &'a Option<String>,
+    tax: Option<f64>,
+    currency: Option<char>,
+    invoice: Option<u32>,
+    target: Option<Target>,
+}
+
+impl<'a> JsonProperties<'a> {
+    /// Take the exported fields from the properties which were found for `tag`.
+    fn new(tag: Option<String>, properties: &'a Properties) -> Self {
+        Self {
+            tag,
+            resolution: properties.resolution,
+            rate: properties.rate,
+            max_hours: properties.max_hours,
+            client: &properties.client,
+            tax: properties.tax,
+            currency: properties.currency,
+            invoice: properties.invoice,
+            target: properties.target,
         }
-        writeln!(w)?;
     }
+}
+
+/// Sums within the JSON export.
+#[derive(Serialize)]
+struct JsonTotal {
+    jobs: usize,
+    hours: f64,
+    pay: Option<f64>,
+}
+
+/// Export a `JobList` as JSON including positions, computed values and the effective configuration.
+/// * `w`: Where the output goes
+/// * `jobs`: Jobs to export
+pub fn export_json<W: std::io::Write>(
+    w: &mut W,
+    jobs: &JobList,
+    context: &Context,
+) -> Result<(), Error> {
+    let mut list = Vec::new();
+    for (pos, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
+        let properties = jobs.configuration.get_checked(&job.tags)?;
+        let (tag, _) = jobs.configuration.get_and_why(&job.tags);
+        let hours = job.hours(properties);
+        list.push(JsonJob {
+            pos: pos + 1,
+            id: &job.id,
+            start: job.start.to_rfc3339(),
+            end: job.end.map(|end| end.to_rfc3339()),
+            message: &job.message,
+            tags: &job.tags.0,
+            invoice: job.invoice(),
+            locked: job.is_locked(),
+            hours,
+            pay: properties.rate.map(|rate| hours * rate),
+            properties: JsonProperties::new(tag, properties),
+        });
+    }
+    let export = JsonExport {
+        schema: JSON_SCHEMA,
+        exported: context.time().to_rfc3339(),
+        configuration: jobs.configuration,
+        jobs: list,
+        total: JsonTotal {
+            jobs: jobs.len(),
+            hours: jobs.hours_overall(),
+            pay: jobs.pay_overall(),
+        },
+    };
+    serde_json::to_writer_pretty(&mut *w, &export).map_err(Error::Json)?;
+    writeln!(w)?;
     Ok(())
 }
@@ -0,0 +1,411 @@
+//! Filter expressions which combine ranges, tags, state and duration of jobs.
+
+use super::prelude::*;
+
+/// Characters which separate the clauses of a filter expression.
+const OPERATORS: &[char] = &['&', '|', '!', '(', ')'];
+
+/// Comparison of a job's duration.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Comparison {
+    /// Shorter than (`<`)
+    Less,
+    /// Not longer than (`<=`)
+    LessEqual,
+    /// Longer than (`>`)
+    Greater,
+    /// Not shorter than (`>=`)
+    GreaterEqual,
+}
+
+/// Term within a filter expression.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Term {
+    /// Jobs within a range.
+    Range(Range),
+    /// Jobs with a tag.
+    Tag(String),
+    /// Jobs with a tag which was given without `#` (and therefore must be known).
+    Word(String),
+    /// Jobs which have not been ended yet.
+    Open,
+    /// Jobs which have been ended.
+    Closed,
+    /// Jobs whose duration compares to the given one.
+    Duration(Comparison, Duration),
+    /// Jobs whose message matches a search.
+    Message(Search),
+    /// Jobs which do not match the term.
+    Not(Box<Term>),
+    /// Jobs which match both terms.
+    And(Box<Term>, Box<Term>),
+    /// Jobs which match any of both terms.
+    Or(Box<Term>, Box<Term>),
+}
+
+/// Filter expression like `march & (client_a | client_b) & !meeting & open & /log(in|out)/`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Filter {
+    /// Expression like it was entered by the user.
+    expression: String,
+    /// Parsed expression.
+    term: Term,
+}
+
+impl Filter {
+    /// Return `true` if `list` shall be parsed as filter expression instead of a simple range.
+    pub fn is_expression(list: &str) -> bool {
+        list.contains(OPERATORS)
+            || list.contains(['<', '>', '#'])
+            || list.trim_start().starts_with(['"', '/'])
+            || matches!(list.trim(), "open" | "closed")
+    }
+    /// Parse filter expression.
+    pub fn parse(expression: &str, context: &Context) -> Result<Self, Error> {
+        let mut parser = Parser {
+            expression,
+            tokens: tokenize(expression),
+            context,
+        };
+        let term = parser.parse_or()?;
+        if let Some(token) = parser.tokens.pop() {
+            return Err(parser.error(&format!("unexpected '{token}'")));
+        }
+        Ok(Self {
+            expression: expression.trim().into(),
+            term,
+        })
+    }
+    /// Replace ranges within the expression by using `resolve` (e.g. to resolve IDs into positions).
+    ///
+    /// Tags which were given without `#` must be within the `known` tags.
+    pub fn resolve<F>(&self, resolve: &F, known: &TagSet) -> Result<Self, Error>
+    where
+        F: Fn(&Range) -> Result<Range, Error>,
+    {
+        let mut words = Vec::new();
+        self.term.collect_words(&mut words);
+        if let Some(word) = words.iter().find(|word| !known.contains(word)) {
+            return Err(Error::FilterFormat(
+                self.expression.clone(),
+                format!("unknown tag '{word}' (use '#{word}' to select it anyway)"),
+            ));
+        }
+        Ok(Self {
+            expression: self.expression.clone(),
+            term: self.term.resolve(resolve)?,
+        })
+    }
+    /// Return `true` if job at position `pos` matches the filter.
+    pub fn matches(&self, pos: usize, job: &Job) -> bool {
+        self.term.matches(pos, job)
+    }
+    /// Return all message searches which are not negated (to highlight their matches).
+    pub fn searches(&self) -> Vec<Search> {
+        let mut searches = Vec::new();
+        self.term.collect_searches(&mut searches);
+        searches
+    }
+}
+
+impl Term {
+    /// Replace ranges by using `resolve`.
+    fn resolve<F>(&self, resolve: &F) -> Result<Self, Error>
+    where
+        F: Fn(&Range) -> Result<Range, Error>,
+    {
+        Ok(match self {
+            Term::Range(range) => Term::Range(resolve(range)?),
+            Term::Not(term) => Term::Not(Box::new(term.resolve(resolve)?)),
+            Term::And(left, right) => Term::And(
+                Box::new(left.resolve(resolve)?),
+                Box::new(right.resolve(resolve)?),
+            ),
+            Term::Or(left, right) => Term::Or(
+                Box::new(left.resolve(resolve)?),
+                Box::new(right.resolve(resolve)?),
+            ),
+            term => term.clone(),
+        })
+    }
+    /// Return `true` if job at position `pos` matches the term.
+    fn matches(&self, pos: usize, job: &Job) -> bool {
+        match self {
+            Term::Range(range) => range.contains(pos, job),
+            Term::Tag(tag) | Term::Word(tag) => job.tags.contains(tag),
+            Term::Open => job.is_open(),
+            Term::Closed => !job.is_open(),
+            Term::Duration(comparison, duration) => {
+                let minutes = job.minutes();
+                let duration = duration.num_minutes();
+                match comparison {
+                    Comparison::Less => minutes < duration,
+                    Comparison::LessEqual => minutes <= duration,
+                    Comparison::Greater => minutes > duration,
+                    Comparison::GreaterEqual => minutes >= duration,
+                }
+            }
+            Term::Message(search) => job
+                .message
+                .as_ref()
+                .is_some_and(|message| search.matches(message)),
+            Term::Not(term) => !term.matches(pos, job),
+            Term::And(left, right) => left.matches(pos, job) && right.matches(pos, job),
+            Term::Or(left, right) => left.matches(pos, job) || right.matches(pos, job),
+        }
+    }
+}
+
+impl Term {
+    /// Collect message searches which are not negated.
+    fn collect_searches(&self, searches: &mut Vec<Search>) {
+        match self {
+            Term::Message(search) => searches.push(search.clone()),
+            Term::And(left, right) | Term::Or(left, right) => {
+                left.collect_searches(searches);
+                right.collect_searches(searches);
+            }
+            _ => (),
+        }
+    }
+    /// Collect tags which were given without `#`.
+    fn collect_words(&self, words: &mut Vec<String>) {
+        match self {
+            Term::Word(word) => words.push(word.clone()),
+            Term::Not(term) => term.collect_words(words),
+            Term::And(left, right) | Term::Or(left, right) => {
+                left.collect_words(words);
+                right.collect_words(words);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+/// Split expression into operators and the (trimmed) clauses between them.
+///
+/// Searches in quotes (`"text"`) or slashes (`/regex/`) are kept together including their delimiters.
+/// Returns tokens in reverse order so that they can be popped.
+fn tokenize(expression: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut clause = String::new();
+    let mut chars = expression.chars();
+    while let Some(c) = chars.next() {
+        if OPERATORS.contains(&c) {
+            if !clause.trim().is_empty() {
+                tokens.push(clause.trim().to_string());
+            }
+            clause.clear();
+            tokens.push(c.to_string());
+        } else if (c == '"' || c == '/') && clause.trim().is_empty() {
+            // read until closing delimiter which may be escaped by a backslash
+            clause = c.to_string();
+            while let Some(n) = chars.next() {
+                clause.push(n);
+                if n == '\\' {
+                    if let Some(n) = chars.next() {
+                        clause.push(n);
+                    }
+                } else if n == c {
+                    break;
+                }
+            }
+        } else {
+            clause.push(c);
+        }
+    }
+    if !clause.trim().is_empty() {
+        tokens.push(clause.trim().to_string());
+    }
+    tokens.reverse();
+    tokens
+}
+
+/// Return the text between the delimiters at the beginning and the end of `clause`.
+///
+/// Returns `None` if the closing delimiter is missing or is not the last character.
+fn unquote(clause: &str, delimiter: char) -> Option<&str> {
+    let inner = clause.strip_prefix(delimiter)?;
+    let mut escaped = false;
+    for (n, c) in inner.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            return (n + 1 == inner.len()).then(|| &inner[..n]);
+        }
+    }
+    None
+}
+
+/// Recursive descent parser of filter expressions.
+struct Parser<'a> {
+    /// Whole expression.
+    expression: &'a str,
+    /// Remaining tokens in reverse order.
+    tokens: Vec<String>,
+    /// Context to resolve ranges with.
+    context: &'a Context,
+}
+
+impl Parser<'_> {
+    /// Take next token if it equals `token`.
+    fn next_if(&mut self, token: &str) -> bool {
+        if self.tokens.last().is_some_and(|next| next == token) {
+            self.tokens.pop();
+            true
+        } else {
+            false
+        }
+    }
+    /// Parse terms separated by `|`.
+    fn parse_or(&mut self) -> Result<Term, Error> {
+        let mut term = self.parse_and()?;
+        while self.next_if("|") {
+            term = Term::Or(Box::new(term), Box::new(self.parse_and()?));
+        }
+        Ok(term)
+    }
+    /// Parse terms separated by `&`.
+    fn parse_and(&mut self) -> Result<Term, Error> {
+        let mut term = self.parse_not()?;
+        while self.next_if("&") {
+            term = Term::And(Box::new(term), Box::new(self.parse_not()?));
+        }
+        Ok(term)
+    }
+    /// Parse term which may be negated by `!` or be put into parentheses.
+    fn parse_not(&mut self) -> Result<Term, Error> {
+        if self.next_if("!") {
+            Ok(Term::Not(Box::new(self.parse_not()?)))
+        } else if self.next_if("(") {
+            let term = self.parse_or()?;
+            if !self.next_if(")") {
+                return Err(self.error("missing ')'"));
+            }
+            Ok(term)
+        } else {
+            self.parse_clause()
+        }
+    }
+    /// Parse a single clause.
+    fn parse_clause(&mut self) -> Result<Term, Error> {
+        let clause = match self.tokens.pop() {
+            Some(clause) if !OPERATORS.iter().any(|op| clause == op.to_string()) => clause,
+            Some(token) => return Err(self.error(&format!("unexpected '{token}'"))),
+            None => return Err(self.error("unexpected end")),
+        };
+        for (op, comparison) in [
+            ("<=", Comparison::LessEqual),
+            (">=", Comparison::GreaterEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ] {
+            if let Some(duration) = clause.strip_prefix(op) {
+                return Ok(Term::Duration(
+                    comparison,
+                    Duration::parse(duration.trim().into())?,
+                ));
+            }
+        }
+        for delimiter in ['"', '/'] {
+            if clause.starts_with(delimiter) {
+                let Some(pattern) = unquote(&clause, delimiter) else {
+                    return Err(self.error(&format!("missing closing {delimiter}")));
+                };
+                return Ok(Term::Message(if delimiter == '"' {
+                    Search::text(&pattern.replace("\\\"", "\""))
+                } else {
+                    Search::regex(pattern).map_err(|err| self.error(&err.to_string()))?
+                }));
+            }
+        }
+        if let Some(tag) = clause.strip_prefix('#') {
+            return Ok(Term::Tag(tag.into()));
+        }
+        Ok(match clause.as_str() {
+            "open" => Term::Open,
+            "closed" => Term::Closed,
+            _ => match Range::parse_simple(&clause, self.context) {
+                Range::None if clause.contains(char::is_whitespace) => {
+                    return Err(Error::RangeFormat(clause))
+                }
+                Range::None => Term::Word(clause),
+                range => Term::Range(range),
+            },
+        })
+    }
+    /// Create error about the expression.
+    fn error(&self, reason: &str) -> Error {
+        Error::FilterFormat(self.expression.into(), reason.into())
+    }
+}
+
+/// Test parsing and matching of filter expressions.
+#[test]
+fn test_filter() {
+    let context = Context::new_test("2023-4-1 12:00");
+    let jobs = [
+        Job::new(
+            "2023-3-1 8:00".into(),
+            Some("2023-3-1 9:00".into()),
+            Some("Client A".into()),
+            Some("client_a".into()),
+        )
+        .unwrap(),
+        Job::new(
+            "2023-3-2 8:00".into(),
+            Some("2023-3-2 12:00".into()),
+            Some("say \"b\"".into()),
+            Some("client_b,meeting".into()),
+        )
+        .unwrap(),
+        Job::new(
+            "2023-2-1 8:00".into(),
+            Some("2023-2-1 9:00".into()),
+            Some("client a again".into()),
+            Some("client_a".into()),
+        )
+        .unwrap(),
+        Job::new(
+            "2023-3-3 8:00".into(),
+            None,
+            Some("client c".into()),
+            Some("client_c".into()),
+        )
+        .unwrap(),
+    ];
+    let filter = |expression: &str| {
+        let filter = Filter::parse(expression, &context).unwrap();
+        jobs.iter()
+            .enumerate()
+            .filter(|(pos, job)| filter.matches(*pos, job))
+            .map(|(pos, _)| pos + 1)
+            .collect::<Vec<usize>>()
+    };
+
+    assert_eq!(filter("march & (client_a | client_b)"), vec![1, 2]);
+    assert_eq!(filter("march & (client_a | client_b) & !meeting"), vec![1]);
+    assert_eq!(filter("open | 1.2."), vec![3, 4]);
+    assert_eq!(filter("closed & >=2h"), vec![2]);
+    assert_eq!(filter("<=1:00 & #client_a & !1.2."), vec![1]);
+    assert_eq!(filter("2-3 & client_a"), vec![3]);
+    assert_eq!(filter("!(client_a|client_b)"), vec![4]);
+    assert_eq!(filter("/client (a|c)/ & open"), vec![4]);
+    assert_eq!(filter("\"CLIENT A\" | \"\\\"b\\\"\""), vec![1, 2, 3]);
+
+    assert!(Filter::parse("(open", &context).is_err());
+    assert!(Filter::parse("open &", &context).is_err());
+    assert!(Filter::parse("open client_a", &context).is_err());
+    assert!(Filter::parse(">2x", &context).is_err());
+    assert!(Filter::parse("/(a/", &context).is_err());
+    assert!(Filter::parse("\"a", &context).is_err());
+    assert!(Filter::parse("\"a\\\"", &context).is_err());
+}
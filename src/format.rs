@@ -121,12 +121,22 @@ pub fn pay_pure(pay: f64) -> String {
     )
 }
 
-/// Format message with style.
+/// Format message with style and highlight matches of the given searches.
 #[cfg(feature = "colors")]
-pub fn message(message: &str, indent: usize) -> String {
+pub fn message(message: &str, indent: usize, searches: &[Search]) -> String {
     let mut output = String::new();
     let lines = message.split('\n');
     for line in lines {
+        let line = highlight(line, searches, |m| {
+            format!(
+                "{}{}{}{}{}",
+                Fg(LightYellow),
+                style::Underline,
+                m,
+                style::NoUnderline,
+                Fg(LightWhite)
+            )
+        });
         if output.is_empty() {
             output += &format!("{}{}{}", style::Bold, Fg(LightWhite), line);
         } else {
@@ -134,12 +144,19 @@ pub fn message(message: &str, indent: usize) -> String {
             for _ in 0..indent {
                 output += " ";
             }
-            output += line;
+            output += &line;
         }
     }
     output + &format!("{}{}", Fg(Reset), style::Reset)
 }
 
+/// Remove any terminal styles and colors from the given output.
+#[cfg(feature = "colors")]
+pub fn unstyled(output: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    regex::bytes::Regex::new(r"\x1b\[[0-9;]*[A-Za-z]")
+        .unwrap()
+        .replace_all(output, &b""[..])
+}
 
 /// Format start date and time with color.
 #[cfg(not(feature = "colors"))]
@@ -173,10 +190,10 @@ pub fn hours_bar(hours: f64, properties: &Properties) -> String {
     }
     if let Some(max_hours) = properties.max_hours {
         if hours > max_hours as f64 {
-            return format!("{}", bar(hours));
+            return bar(hours);
         }
     }
-    format!("{}", bar(hours))
+    bar(hours)
 }
 
 /// Format hours (considering resolution) with style & color.
@@ -184,7 +201,7 @@ pub fn hours_bar(hours: f64, properties: &Properties) -> String {
 pub fn hours(hours: f64, properties: &Properties) -> String {
     if let Some(max_hours) = properties.max_hours {
         if hours > max_hours as f64 {
-            return format!("{}", hours);
+            return hours.to_string();
         }
     }
     hours_pure(hours)
@@ -199,25 +216,31 @@ pub fn hours_pure(hours: f64) -> String {
 /// Format exact payment with style & color.
 #[cfg(not(feature = "colors"))]
 pub fn pay_pure(pay: f64) -> String {
-    format!("{}", pay.separated_string())
+    pay.separated_string()
 }
 
-/// Format message with style.
+/// Remove any terminal styles and colors from the given output.
 #[cfg(not(feature = "colors"))]
-pub fn message(message: &str, indent: usize) -> String {
+pub fn unstyled(output: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    output.into()
+}
+
+/// Format message and put matches of the given searches into brackets.
+#[cfg(not(feature = "colors"))]
+pub fn message(message: &str, indent: usize, searches: &[Search]) -> String {
     let mut output = String::new();
     let lines = message.split('\n');
     for line in lines {
+        let line = highlight(line, searches, |m| format!("[{m}]"));
         if output.is_empty() {
-            output += &format!("{}", line);
+            output += &line;
         } else {
             output += "\n";
             for _ in 0..indent {
                 output += " ";
             }
-            output += line;
+            output += &line;
         }
     }
     output
 }
-
@@ -0,0 +1,379 @@
+//! iCalendar (RFC 5545) export and import of jobs.
+
+use super::prelude::*;
+use chrono::{NaiveDate, NaiveDateTime};
+use itertools::Itertools;
+use regex::Regex;
+use std::io::Read;
+
+/// Suffix of the `UID` of exported events (prefixed by the job's ID).
+const UID_SUFFIX: &str = "@jobber";
+
+/// Export jobs as events of an iCalendar.
+/// * `w`: Where the output goes
+/// * `jobs`: Jobs to export
+pub fn export_ics<W: std::io::Write>(
+    w: &mut W,
+    jobs: &JobList,
+    context: &Context,
+) -> Result<(), Error> {
+    let stamp = context.time().format_utc("%Y%m%dT%H%M%SZ");
+    write_line(w, "BEGIN:VCALENDAR")?;
+    write_line(w, "VERSION:2.0")?;
+    write_line(
+        w,
+        &format!("PRODID:-//jobber//jobber {}//EN", env!("CARGO_PKG_VERSION")),
+    )?;
+    for (_, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
+        let end = job.end.unwrap_or_else(|| context.time());
+        write_line(w, "BEGIN:VEVENT")?;
+        write_line(w, &format!("UID:{}{UID_SUFFIX}", job.id))?;
+        write_line(w, &format!("DTSTAMP:{stamp}"))?;
+        write_line(
+            w,
+            &format!("DTSTART:{}", job.start.format_utc("%Y%m%dT%H%M%SZ")),
+        )?;
+        write_line(w, &format!("DTEND:{}", end.format_utc("%Y%m%dT%H%M%SZ")))?;
+        if let Some(message) = &job.message {
+            write_line(w, &format!("SUMMARY:{}", escape(message)))?;
+        }
+        if !job.tags.is_empty() {
+            write_line(
+                w,
+                &format!(
+                    "CATEGORIES:{}",
+                    job.tags.0.iter().map(|t| escape(t)).join(",")
+                ),
+            )?;
+        }
+        write_line(w, "END:VEVENT")?;
+    }
+    write_line(w, "END:VCALENDAR")?;
+    Ok(())
+}
+
+/// Write a content line and fold it after 75 octets.
+fn write_line<W: std::io::Write>(w: &mut W, line: &str) -> Result<(), Error> {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            write!(w, "\r\n ")?;
+            length = 1;
+        }
+        write!(w, "{c}")?;
+        length += c.len_utf8();
+    }
+    write!(w, "\r\n")?;
+    Ok(())
+}
+
+/// Escape text value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Split text value at unescaped `separator` and unescape the parts.
+fn unescape(text: &str, separator: Option<char>) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') | Some('N') => parts.last_mut().unwrap().push('\n'),
+                Some(c) => parts.last_mut().unwrap().push(c),
+                None => (),
+            },
+            c if Some(c) == separator => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    parts
+}
+
+/// Content line of an iCalendar.
+struct Property {
+    /// Upper case name.
+    name: String,
+    /// Parameters like `VALUE=DATE` with upper case names.
+    params: Vec<String>,
+    value: String,
+}
+
+impl Property {
+    /// Parse unfolded content line `NAME;PARAM=...:VALUE`.
+    fn parse(line: usize, content: &str) -> Result<Self, String> {
+        let mut quoted = false;
+        let colon = content
+            .char_indices()
+            .find(|(_, c)| {
+                if *c == '"' {
+                    quoted = !quoted;
+                }
+                !quoted && *c == ':'
+            })
+            .map(|(n, _)| n)
+            .ok_or_else(|| format!("line {line}: missing ':' in '{content}'"))?;
+        let mut params = content[..colon].split(';');
+        Ok(Self {
+            name: params.next().unwrap_or_default().trim().to_uppercase(),
+            params: params
+                .map(|p| match p.split_once('=') {
+                    Some((name, value)) => format!("{}={value}", name.trim().to_uppercase()),
+                    None => p.to_uppercase(),
+                })
+                .collect(),
+            value: content[colon + 1..].to_string(),
+        })
+    }
+    /// Get value of parameter `name` (without quotes).
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find_map(|p| {
+            p.strip_prefix(name)
+                .and_then(|p| p.strip_prefix('='))
+                .map(|value| value.trim_matches('"'))
+        })
+    }
+    /// Get time zone given by parameter `TZID` if it is UTC or an offset.
+    ///
+    /// Named time zones (like `Europe/Berlin`) return `None` so that the time is taken in the time zone
+    /// given by the user.
+    fn zone(&self) -> Option<Zone> {
+        let tzid = self.param("TZID")?.trim_start_matches('/');
+        match tzid.to_uppercase().as_str() {
+            "UTC" | "GMT" | "ETC/UTC" | "ETC/GMT" => Some(Zone::Utc),
+            _ => match Zone::parse(tzid) {
+                Ok(Zone::Local) | Err(_) => None,
+                Ok(zone) => Some(zone),
+            },
+        }
+    }
+    /// Parse date or date and time value (floating times are taken in `zone`).
+    fn date_time(&self, zone: &Zone) -> Result<DateTime, String> {
+        let value = self.value.trim();
+        let naive = if self
+            .param("VALUE")
+            .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+            || value.len() == 8
+        {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+        } else {
+            NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        }
+        .map_err(|err| format!("can not parse {} '{value}' ({err})", self.name))?;
+        let tzid = self.zone();
+        if value.ends_with('Z') {
+            &Zone::Utc
+        } else {
+            tzid.as_ref().unwrap_or(zone)
+        }
+        .convert(&naive)
+        .ok_or_else(|| format!("{} '{value}' does not exist in time zone", self.name))
+    }
+    /// Parse duration value like `PT1H30M`.
+    fn duration(&self) -> Result<chrono::Duration, String> {
+        let re =
+            Regex::new(r"^([+-])?P(?:(\d+)W)?(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)S)?)?$")
+                .unwrap();
+        let value = self.value.trim();
+        let cap = re
+            .captures(value)
+            .ok_or_else(|| format!("can not parse DURATION '{value}'"))?;
+        let number = |n: usize| cap.get(n).map_or(0, |m| m.as_str().parse::<i64>().unwrap());
+        let duration = chrono::Duration::weeks(number(2))
+            + chrono::Duration::days(number(3))
+            + chrono::Duration::hours(number(4))
+            + chrono::Duration::minutes(number(5))
+            + chrono::Duration::seconds(number(6));
+        Ok(if cap.get(1).map(|m| m.as_str()) == Some("-") {
+            -duration
+        } else {
+            duration
+        })
+    }
+}
+
+/// Import all events of an iCalendar as jobs.
+///
+/// Returns a list of errors (one per event) if any event could not be imported.
+pub fn import_ics<R: Read>(mut reader: R, zone: &Zone) -> Result<Vec<Job>, Vec<String>> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|err| vec![err.to_string()])?;
+
+    // unfold lines
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(folded), Some((_, last))) => last.push_str(folded),
+            _ if line.trim().is_empty() => (),
+            _ => lines.push((n + 1, line.to_string())),
+        }
+    }
+
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    // stack of currently open components
+    let mut components: Vec<String> = Vec::new();
+    let mut event: Vec<Property> = Vec::new();
+    let mut begin = 0;
+    for (line, content) in lines {
+        let property = match Property::parse(line, &content) {
+            Ok(property) => property,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_uppercase();
+                if component == "VEVENT" {
+                    event.clear();
+                    begin = line;
+                }
+                components.push(component);
+            }
+            "END" => {
+                let component = components.pop();
+                if component.as_deref() == Some("VEVENT") {
+                    match parse_event(&event, zone) {
+                        Ok(job) => jobs.push(job),
+                        Err(err) => errors.push(format!("line {begin}: {err}")),
+                    }
+                }
+            }
+            // ignore properties of sub components like VALARM
+            _ if components.last().map(String::as_str) == Some("VEVENT") => event.push(property),
+            _ => (),
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Create a job from the properties of an event.
+fn parse_event(event: &[Property], zone: &Zone) -> Result<Job, String> {
+    let mut id = None;
+    let mut start = None;
+    let mut end = None;
+    let mut duration = None;
+    let mut all_day = false;
+    let mut message = None;
+    let mut tags = TagSet::new();
+    for property in event {
+        match property.name.as_str() {
+            "UID" => {
+                if let Some(uid) = property.value.trim().strip_suffix(UID_SUFFIX) {
+                    id = JobId::parse(uid);
+                }
+            }
+            "DTSTART" => {
+                all_day = property.value.trim().len() == 8;
+                start = Some(property.date_time(zone)?);
+            }
+            "DTEND" => end = Some(property.date_time(zone)?),
+            "DURATION" => duration = Some(property.duration()?),
+            "SUMMARY" => message = unescape(&property.value, None).pop(),
+            "CATEGORIES" => {
+                for tag in unescape(&property.value, Some(',')) {
+                    let tag = tag.trim();
+                    if tag.contains(char::is_whitespace) {
+                        return Err(format!("category '{tag}' contains white space"));
+                    } else if !tag.is_empty() {
+                        tags.insert(tag);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let start = start.ok_or("event without DTSTART")?;
+    let end = match (end, duration) {
+        (Some(end), _) => end,
+        (None, Some(duration)) => start + duration,
+        // all day events last one day by default
+        (None, None) if all_day => start + chrono::Duration::days(1),
+        (None, None) => return Err("event without DTEND or DURATION".into()),
+    };
+    let message = message.filter(|message| !message.is_empty());
+    let mut job = Job::new(start, Some(end), message, Some(tags)).map_err(|err| err.to_string())?;
+    if let Some(id) = id {
+        job.id = id;
+    }
+    Ok(job)
+}
+
+/// Test exporting jobs and importing them again.
+#[test]
+fn test_ics() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut job = Job::new(
+        "2023-1-31 8:00".into(),
+        Some("2023-1-31 10:30".into()),
+        Some("talk about invoice; budget, costs\nand more about the next steps in our long running project".into()),
+        Some(TagSet::from("meeting,client")),
+    )
+    .unwrap();
+    job.id = JobId::from("0123456789abcdef");
+    let configuration = Configuration::default();
+    let list = JobList::new(vec![(0, &job)], &configuration);
+    let mut output = Vec::new();
+    export_ics(&mut output, &list, &context).unwrap();
+    let ics = String::from_utf8(output).unwrap();
+    assert!(ics.contains("UID:0123456789abcdef@jobber\r\n"));
+    assert!(ics.contains("CATEGORIES:meeting,client\r\n"));
+    assert!(ics.lines().all(|line| line.trim_end().len() <= 75));
+    assert!(ics.contains("\r\n "));
+
+    let jobs = import_ics(ics.as_bytes(), &Zone::Local).unwrap();
+    assert_eq!(jobs, vec![job]);
+}
+
+/// Test importing events of another calendar.
+#[test]
+fn test_import_ics() {
+    let ics = "BEGIN:VCALENDAR\n\
+               BEGIN:VEVENT\n\
+               UID:abc@example.com\n\
+               DTSTART;TZID=Europe/Berlin:20230131T080000\n\
+               DURATION:PT1H30M\n\
+               SUMMARY:Daily\n\
+               BEGIN:VALARM\n\
+               DURATION:PT15M\n\
+               END:VALARM\n\
+               END:VEVENT\n\
+               BEGIN:VEVENT\n\
+               DTSTART;VALUE=DATE:20230201\n\
+               SUMMARY:Workshop\n\
+               END:VEVENT\n\
+               BEGIN:VEVENT\n\
+               SUMMARY:broken\n\
+               END:VEVENT\n\
+               END:VCALENDAR\n";
+    let errors = import_ics(ics.as_bytes(), &Zone::Utc).unwrap_err();
+    assert_eq!(errors, vec!["line 15: event without DTSTART".to_string()]);
+
+    // named time zones fall back to the given one
+    let ics = ics.replace("BEGIN:VEVENT\nSUMMARY:broken\nEND:VEVENT\n", "");
+    let jobs = import_ics(ics.as_bytes(), &Zone::parse("+01:00").unwrap()).unwrap();
+    assert_eq!(jobs[0].start.to_rfc3339(), "2023-01-31T07:00:00+00:00");
+
+    let ics = ics.replace("TZID=Europe/Berlin", "TZID=UTC");
+    let jobs = import_ics(ics.as_bytes(), &Zone::parse("+02:00").unwrap()).unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].start.to_rfc3339(), "2023-01-31T08:00:00+00:00");
+    assert_eq!(
+        jobs[0].end,
+        Some(jobs[0].start + chrono::Duration::minutes(90))
+    );
+    assert_eq!(jobs[1].message, Some("Workshop".into()));
+    assert_eq!(jobs[1].end, Some(jobs[1].start + chrono::Duration::days(1)));
+}
@@ -0,0 +1,597 @@
+//! Import of jobs from files.
+
+use super::prelude::*;
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+};
+
+/// Time zone in which imported date and times are given.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    /// Local time zone.
+    Local,
+    /// UTC.
+    Utc,
+    /// Fixed offset from UTC.
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Parse time zone from `local`, `utc` or an offset like `+02:00`.
+    pub fn parse(zone: &str) -> Result<Self, Error> {
+        match zone.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" | "z" => Ok(Self::Utc),
+            offset => {
+                let re = Regex::new(r"^([+-])(\d{1,2}):?(\d{2})?$").unwrap();
+                if let Some(cap) = re.captures(offset) {
+                    let seconds = cap[2].parse::<i32>().unwrap() * 3600
+                        + cap.get(3).map_or(0, |m| m.as_str().parse::<i32>().unwrap()) * 60;
+                    let seconds = if &cap[1] == "-" { -seconds } else { seconds };
+                    if let Some(offset) = FixedOffset::east_opt(seconds) {
+                        return Ok(Self::Fixed(offset));
+                    }
+                }
+                Err(Error::TimeZoneFormat(zone.into()))
+            }
+        }
+    }
+    /// Convert naive date and time given in this time zone.
+    pub fn convert(&self, naive: &NaiveDateTime) -> Option<DateTime> {
+        match self {
+            Self::Local => DateTime::try_from_local(naive),
+            Self::Utc => Some(Utc.from_utc_datetime(naive).into()),
+            Self::Fixed(offset) => offset
+                .from_local_datetime(naive)
+                .single()
+                .map(|date_time| date_time.with_timezone(&Utc).into()),
+        }
+    }
+}
+
+/// Which fields to put into quotes when writing CSV.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quoting {
+    /// Quote all fields.
+    All,
+    /// Quote text fields but not numbers.
+    Strings,
+    /// Quote fields only if they contain delimiters, quotes or line breaks.
+    Necessary,
+}
+
+/// How to read or write a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvDialect {
+    /// Character which separates the fields.
+    pub delimiter: char,
+    /// Format of date and time (see `chrono::format::strftime`).
+    pub date_format: String,
+    /// Time zone of date and time (when reading).
+    pub zone: Zone,
+    /// Character which separates the fraction of numbers (when writing).
+    pub decimal_separator: char,
+    /// Which fields to put into quotes (when writing).
+    pub quoting: Quoting,
+    /// Write a header line with the column names.
+    pub header: bool,
+}
+
+impl Default for CsvDialect {
+    /// Dialect which is written by [export_csv].
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            date_format: "%m/%d/%Y %H:%M".into(),
+            zone: Zone::Local,
+            decimal_separator: '.',
+            quoting: Quoting::Strings,
+            header: true,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Parse date and time with the configured format or RFC3339 as fallback.
+    fn parse_date_time(&self, date_time: &str) -> Result<DateTime, String> {
+        if let Ok(date_time) = DateTime::from_rfc3339(date_time) {
+            return Ok(date_time);
+        }
+        let naive = NaiveDateTime::parse_from_str(date_time, &self.date_format)
+            .or_else(|err| {
+                chrono::NaiveDate::parse_from_str(date_time, &self.date_format)
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                    .map_err(|_| err)
+            })
+            .map_err(|err| format!("can not parse date '{date_time}' ({err})"))?;
+        self.zone
+            .convert(&naive)
+            .ok_or_else(|| format!("date '{date_time}' does not exist in time zone"))
+    }
+}
+
+/// Supported formats of files to import.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportFormat {
+    /// CSV database of legacy Ruby *jobber* version.
+    Legacy,
+    /// CSV file with the given columns (or the column names given in the header line).
+    Csv {
+        dialect: CsvDialect,
+        columns: Option<Columns>,
+    },
+    /// iCalendar file which events (with floating times in the given time zone) become jobs.
+    Ics { zone: Zone },
+    /// Timewarrior data file.
+    Timewarrior,
+    /// Timeclock file of *hledger* or *ledger* with times in the given time zone.
+    Timeclock { zone: Zone },
+    /// CSV export of a time tracking service.
+    Tracker {
+        tracker: Tracker,
+        dialect: CsvDialect,
+    },
+}
+
+/// Time tracking services which CSV exports (detailed reports) can be imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tracker {
+    Toggl,
+    Clockify,
+}
+
+impl std::fmt::Display for Tracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tracker::Toggl => write!(f, "Toggl"),
+            Tracker::Clockify => write!(f, "Clockify"),
+        }
+    }
+}
+
+/// Read jobs from a file of the given format.
+pub fn import(filename: &str, format: &ImportFormat) -> Result<Vec<Job>, Error> {
+    let file = File::options()
+        .read(true)
+        .open(filename)
+        .map_err(Error::Io)?;
+    match format {
+        ImportFormat::Legacy => legacy_import(file),
+        ImportFormat::Csv { dialect, columns } => import_csv(file, dialect, columns)
+            .map_err(|errors| Error::Import(filename.into(), errors)),
+        ImportFormat::Ics { zone } => {
+            import_ics(file, zone).map_err(|errors| Error::Import(filename.into(), errors))
+        }
+        ImportFormat::Timewarrior => {
+            import_timewarrior(file).map_err(|errors| Error::Import(filename.into(), errors))
+        }
+        ImportFormat::Timeclock { zone } => {
+            import_timeclock(file, zone).map_err(|errors| Error::Import(filename.into(), errors))
+        }
+        ImportFormat::Tracker { tracker, dialect } => import_tracker(file, *tracker, dialect)
+            .map_err(|errors| Error::Import(filename.into(), errors)),
+    }
+}
+
+/// Import legacy jobber database from CSV.
+fn legacy_import<R: Read>(reader: R) -> Result<Vec<Job>, Error> {
+    let reader = BufReader::new(reader);
+    let mut jobs = Vec::new();
+    let re = Regex::new(r#""(.*)";"(.*)";"(.*)";"(.*)"$"#).unwrap();
+    for line in reader.lines() {
+        for cap in re.captures_iter(&line?) {
+            let start = DateTime::from_rfc3339(&cap[1])?;
+            let end = cap[2].to_string();
+            let end = if end.is_empty() {
+                None
+            } else {
+                Some(DateTime::from_rfc3339(&end)?)
+            };
+            let message = cap[3].to_string();
+            let message = if message.is_empty() {
+                None
+            } else {
+                Some(message)
+            };
+            let tags = cap[4].to_string();
+            let tags = if tags.is_empty() {
+                None
+            } else {
+                Some(tags.as_str().into())
+            };
+            jobs.push(Job::new(start, end, message, tags)?);
+        }
+    }
+    Ok(jobs)
+}
+
+/// Import jobs from CSV which uses the given `columns` or the column names in the header line.
+///
+/// Returns a list of errors (one per line) if any line could not be imported.
+pub fn import_csv<R: Read>(
+    mut reader: R,
+    dialect: &CsvDialect,
+    columns: &Option<Columns>,
+) -> Result<Vec<Job>, Vec<String>> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|err| vec![err.to_string()])?;
+    let mut records = parse_csv(&text, dialect.delimiter)?.into_iter().peekable();
+
+    // use given columns and skip header or read columns from header
+    let header = records.peek().map(|(_, fields)| {
+        fields
+            .iter()
+            .map(|field| Column::from(field.trim()))
+            .collect::<Result<Vec<Column>, Error>>()
+    });
+    let columns: Vec<Column> = match (columns, header) {
+        (Some(columns), Some(Ok(_))) => {
+            records.next();
+            columns.iter().cloned().collect()
+        }
+        (Some(columns), _) => columns.iter().cloned().collect(),
+        (None, Some(Ok(header))) => {
+            records.next();
+            header
+        }
+        (None, Some(Err(err))) => return Err(vec![format!("line 1: {err}")]),
+        (None, None) => Vec::new(),
+    };
+
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for (line, fields) in records {
+        match parse_job(&columns, &fields, dialect) {
+            Ok(job) => jobs.push(job),
+            Err(err) => errors.push(format!("line {line}: {err}")),
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Create a job from the fields of a CSV record.
+fn parse_job(columns: &[Column], fields: &[String], dialect: &CsvDialect) -> Result<Job, String> {
+    if fields.len() != columns.len() {
+        return Err(format!(
+            "expected {} fields but found {}",
+            columns.len(),
+            fields.len()
+        ));
+    }
+    let mut id = None;
+    let mut start = None;
+    let mut end = None;
+    let mut duration = None;
+    let mut hours = None;
+    let mut message = None;
+    let mut tags = TagSet::new();
+    for (column, field) in columns.iter().zip(fields) {
+        let field = field.trim();
+        if field.is_empty() {
+            continue;
+        }
+        match column {
+            Column::Id => {
+                id = Some(JobId::parse(field).ok_or_else(|| format!("invalid ID '{field}'"))?)
+            }
+            Column::Start => start = Some(dialect.parse_date_time(field)?),
+            Column::End => end = Some(dialect.parse_date_time(field)?),
+            Column::Duration => duration = Some(parse_hours(field)?),
+            Column::Hours => hours = Some(parse_hours(field)?),
+            Column::Message => message = Some(field.to_string()),
+            Column::Tags => {
+                for tag in field.split(',').map(|tag| tag.trim()) {
+                    if tag.contains(char::is_whitespace) {
+                        return Err(format!("tag '{tag}' contains white space"));
+                    } else if !tag.is_empty() {
+                        tags.insert(tag);
+                    }
+                }
+            }
+            // computed values can not be imported
+            Column::Pos | Column::Pay | Column::Rate | Column::MaxHours | Column::Resolution => (),
+        }
+    }
+    let start = start.ok_or("missing start")?;
+    let end = match (end, duration.or(hours)) {
+        (Some(end), _) => end,
+        (None, Some(duration)) => start + duration,
+        (None, None) => return Err("missing end, duration or hours".into()),
+    };
+    let mut job = Job::new(start, Some(end), message, Some(tags)).map_err(|err| err.to_string())?;
+    if let Some(id) = id {
+        job.id = id;
+    }
+    Ok(job)
+}
+
+/// Parse a duration or fractional hours (like `1.3333`).
+fn parse_hours(hours: &str) -> Result<chrono::Duration, String> {
+    if let Ok(duration) = Duration::parse(hours.into()) {
+        return Ok(duration.into());
+    }
+    hours
+        .replace(',', ".")
+        .parse::<f64>()
+        .map(|hours| chrono::Duration::minutes((hours * 60.0).round() as i64))
+        .map_err(|_| format!("can not parse duration '{hours}'"))
+}
+
+/// Import CSV export of a time tracking service.
+///
+/// Columns are found by their names in the header line (e.g. `Start date` or `Duration`).
+/// Client, project and tags become tags and the description becomes the message.
+/// Returns a list of errors (one per line) if any line could not be imported.
+pub fn import_tracker<R: Read>(
+    mut reader: R,
+    tracker: Tracker,
+    dialect: &CsvDialect,
+) -> Result<Vec<Job>, Vec<String>> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|err| vec![err.to_string()])?;
+    let mut records = parse_csv(&text, dialect.delimiter)?.into_iter();
+    let header: HashMap<String, usize> = match records.next() {
+        Some((_, fields)) => fields
+            .iter()
+            .enumerate()
+            .map(|(n, field)| (field.trim().to_lowercase(), n))
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+    for column in ["start date", "start time"] {
+        if !header.contains_key(column) {
+            return Err(vec![format!(
+                "line 1: missing column '{column}' (is this a {tracker} export?)"
+            )]);
+        }
+    }
+
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for (line, fields) in records {
+        // get non empty field of the first available column
+        let get = |columns: &[&str]| {
+            columns
+                .iter()
+                .filter_map(|column| header.get(*column))
+                .filter_map(|n| fields.get(*n))
+                .map(|field| field.trim())
+                .find(|field| !field.is_empty())
+        };
+        let job = || -> Result<Job, String> {
+            let start_date = get(&["start date"]).ok_or("missing start date")?;
+            let start = parse_date_and_time(start_date, get(&["start time"]), &dialect.zone)?;
+            let end = match (get(&["end date"]), get(&["end time"])) {
+                (date, Some(time)) => {
+                    parse_date_and_time(date.unwrap_or(start_date), Some(time), &dialect.zone)?
+                }
+                _ => {
+                    if let Some(duration) = get(&["duration", "duration (h)"]) {
+                        start + parse_clock_duration(duration)?
+                    } else if let Some(hours) = get(&["duration (decimal)"]) {
+                        start + parse_hours(hours)?
+                    } else {
+                        return Err("missing end or duration".into());
+                    }
+                }
+            };
+            let mut tags = TagSet::new();
+            for name in [get(&["client"]), get(&["project"])].into_iter().flatten() {
+                tags.insert(&tag_name(name));
+            }
+            for tag in get(&["tags"]).unwrap_or_default().split(',') {
+                if !tag.trim().is_empty() {
+                    tags.insert(&tag_name(tag));
+                }
+            }
+            let message = get(&["description"]).map(|message| message.to_string());
+            Job::new(start, Some(end), message, Some(tags)).map_err(|err| err.to_string())
+        };
+        match job() {
+            Ok(job) => jobs.push(job),
+            Err(err) => errors.push(format!("line {line}: {err}")),
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Make a tag from a name by replacing white space with `-`.
+fn tag_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<&str>>().join("-")
+}
+
+/// Parse date (like `2023-01-31` or `01/31/2023`) and time (like `08:00:00` or `08:00 AM`).
+fn parse_date_and_time(date: &str, time: Option<&str>, zone: &Zone) -> Result<DateTime, String> {
+    let date = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+        .ok_or_else(|| format!("can not parse date '{date}'"))?;
+    let time = match time {
+        Some(time) => ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(time, format).ok())
+            .ok_or_else(|| format!("can not parse time '{time}'"))?,
+        None => NaiveTime::MIN,
+    };
+    let naive = date.and_time(time);
+    zone.convert(&naive)
+        .ok_or_else(|| format!("date '{naive}' does not exist in time zone"))
+}
+
+/// Parse duration like `02:30:00`.
+fn parse_clock_duration(duration: &str) -> Result<chrono::Duration, String> {
+    let parts = duration
+        .split(':')
+        .map(|part| part.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| format!("can not parse duration '{duration}'"))?;
+    match parts[..] {
+        [hours, minutes, seconds] => Ok(chrono::Duration::seconds(
+            hours * 3600 + minutes * 60 + seconds,
+        )),
+        [hours, minutes] => Ok(chrono::Duration::minutes(hours * 60 + minutes)),
+        _ => Err(format!("can not parse duration '{duration}'")),
+    }
+}
+
+/// Split CSV text into records of fields together with the line number each record starts at.
+///
+/// Fields may be quoted with `"` and then contain delimiters, line breaks and doubled quotes.
+fn parse_csv(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, Vec<String>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.trim().is_empty() {
+            field.clear();
+            quoted = true;
+        } else if c == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else if c == '\n' {
+            fields.push(std::mem::take(&mut field));
+            // ignore empty lines
+            if fields.len() > 1 || !fields[0].trim().is_empty() {
+                records.push((start, std::mem::take(&mut fields)));
+            }
+            fields.clear();
+            line += 1;
+            start = line;
+        } else if c != '\r' {
+            field.push(c);
+        }
+    }
+    if quoted {
+        return Err(vec![format!("line {start}: missing closing quote")]);
+    }
+    if !fields.is_empty() || !field.trim().is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    Ok(records)
+}
+
+/// Test reading back the default CSV export.
+#[test]
+fn test_import_csv() {
+    let csv = "\"Tags\",\"Start\",\"Hours\",\"Message\"\n\
+               \"\",\"03/04/2023 08:15\",2.5,\"What I did \"\"early\"\"\nthis morning\"\n\
+               \"meeting,client\",\"03/05/2023 21:24\",2,\"meeting\"\n";
+    let jobs = import_csv(csv.as_bytes(), &CsvDialect::default(), &None).unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].start, "2023-03-04 08:15".into());
+    assert_eq!(jobs[0].end, Some("2023-03-04 10:45".into()));
+    assert_eq!(
+        jobs[0].message,
+        Some("What I did \"early\"\nthis morning".into())
+    );
+    assert_eq!(jobs[1].tags, TagSet::from("meeting,client"));
+}
+
+/// Test reading Toggl and Clockify exports.
+#[test]
+fn test_import_tracker() {
+    let toggl = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                 Me,me@example.com,ACME Corp,Web Site,,Fix menu,Yes,2023-01-31,08:00:00,2023-01-31,10:30:00,02:30:00,\"urgent, bug\"\n";
+    let jobs = import_tracker(toggl.as_bytes(), Tracker::Toggl, &CsvDialect::default()).unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].start, "2023-01-31 08:00".into());
+    assert_eq!(jobs[0].end, Some("2023-01-31 10:30".into()));
+    assert_eq!(jobs[0].tags, TagSet::from("ACME-Corp,Web-Site,urgent,bug"));
+    assert_eq!(jobs[0].message, Some("Fix menu".into()));
+
+    let clockify = "Project,Client,Description,Task,User,Email,Tags,Billable,Start Date,Start Time,Duration (h),Duration (decimal)\n\
+                    Web Site,,Meeting,,Me,me@example.com,,Yes,01/31/2023,01:00:00 PM,01:15:00,1.25\n\
+                    Web Site,,Broken,,Me,me@example.com,,Yes,31/01/2023,01:00:00 PM,01:15:00,1.25\n";
+    let errors = import_tracker(
+        clockify.as_bytes(),
+        Tracker::Clockify,
+        &CsvDialect::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["line 3: can not parse date '31/01/2023'".to_string()]
+    );
+    let clockify = clockify.lines().take(2).collect::<Vec<&str>>().join("\n");
+    let jobs = import_tracker(
+        clockify.as_bytes(),
+        Tracker::Clockify,
+        &CsvDialect::default(),
+    )
+    .unwrap();
+    assert_eq!(jobs[0].start, "2023-01-31 13:00".into());
+    assert_eq!(jobs[0].end, Some("2023-01-31 14:15".into()));
+    assert_eq!(jobs[0].tags, TagSet::from("Web-Site"));
+
+    let errors = import_tracker(
+        "a,b\n1,2\n".as_bytes(),
+        Tracker::Toggl,
+        &CsvDialect::default(),
+    )
+    .unwrap_err();
+    assert!(errors[0].contains("Toggl"));
+}
+
+/// Test reporting errors by line.
+#[test]
+fn test_import_csv_errors() {
+    let csv = "2023-03-04;08:00;x\n\
+               2023-03-04;y;z\n\
+               2023-03-04\n";
+    let dialect = CsvDialect {
+        delimiter: ';',
+        date_format: "%Y-%m-%d".into(),
+        zone: Zone::Utc,
+        ..Default::default()
+    };
+    let errors = import_csv(
+        csv.as_bytes(),
+        &dialect,
+        &Some(Columns::parse("start,duration,message").unwrap()),
+    )
+    .unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("line 2:"));
+    assert!(errors[1].starts_with("line 3:"));
+
+    let errors = import_csv(
+        "3f2a;2023-03-04;08:00\n".as_bytes(),
+        &dialect,
+        &Some(Columns::parse("id,start,duration").unwrap()),
+    )
+    .unwrap_err();
+    assert_eq!(errors, vec!["line 1: invalid ID '3f2a'".to_string()]);
+}
@@ -0,0 +1,321 @@
+//! Invoices about the jobs of a client.
+
+use super::prelude::*;
+use itertools::Itertools;
+use separator::Separatable;
+
+/// How jobs are summarized into the items of an invoice.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Grouping {
+    /// One item per day
+    #[default]
+    Day,
+    /// One item per message
+    Message,
+}
+
+impl std::fmt::Display for Grouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grouping::Day => write!(f, "day"),
+            Grouping::Message => write!(f, "message"),
+        }
+    }
+}
+
+/// Invoice about finished jobs which have not been billed so far.
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    /// Number of the invoice.
+    pub number: u32,
+    /// Tags of the billed jobs which configuration provides client address and tax rate.
+    pub tags: TagSet,
+    /// Positions of the billed jobs.
+    pub positions: Positions,
+    /// How jobs are summarized into items.
+    pub grouping: Grouping,
+    /// Format of the invoice ([OutputFormat::Text] writes Markdown).
+    pub format: OutputFormat,
+}
+
+/// Item within an invoice.
+struct Item {
+    /// Day of the jobs if grouped by day.
+    date: Option<String>,
+    /// Messages of the jobs.
+    description: String,
+    /// Hours considering resolution.
+    hours: f64,
+    /// Hours multiplied by rate.
+    amount: f64,
+}
+
+/// Sums of an invoice.
+struct Sums {
+    hours: f64,
+    net: f64,
+    /// Tax rate and tax if any tax rate is configured.
+    tax: Option<(f64, f64)>,
+    total: f64,
+}
+
+/// Format amount of money with two decimals after the sign and the currency.
+fn money(amount: f64, currency: char) -> String {
+    let cents = (amount * 100.0).round() as i64;
+    format!(
+        "{}{currency}{}.{:02}",
+        if cents < 0 { "-" } else { "" },
+        (cents.abs() / 100).separated_string(),
+        cents.abs() % 100
+    )
+}
+
+impl Invoice {
+    /// Summarize jobs into items.
+    fn items(&self, jobs: &JobList) -> Vec<Item> {
+        // items in order of their first job together with the key they are grouped by
+        let mut items: Vec<(String, Item)> = Vec::new();
+        for (_, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
+            let properties = jobs.get_configuration(&job.tags);
+            let hours = job.hours(properties);
+            let amount = hours * properties.rate.unwrap_or_default();
+            let message = job.message.clone().unwrap_or_default().replace('\n', " ");
+            let key = match self.grouping {
+                Grouping::Day => job.start.format("%Y-%m-%d"),
+                Grouping::Message => message.clone(),
+            };
+            if let Some((_, item)) = items.iter_mut().find(|(k, _)| *k == key) {
+                // collect different messages of the same day
+                if !item.description.split("; ").contains(&message.as_str()) {
+                    item.description = format!("{}; {message}", item.description);
+                }
+                item.hours += hours;
+                item.amount += amount;
+            } else {
+                let date = (self.grouping == Grouping::Day).then(|| key.clone());
+                items.push((
+                    key,
+                    Item {
+                        date,
+                        description: message,
+                        hours,
+                        amount,
+                    },
+                ));
+            }
+        }
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+    /// Sum up items and add taxes.
+    fn sums(items: &[Item], properties: &Properties) -> Sums {
+        let hours = items.iter().map(|item| item.hours).sum();
+        let net = items.iter().map(|item| item.amount).sum();
+        let tax = properties.tax.map(|rate| (rate, net * rate / 100.0));
+        Sums {
+            hours,
+            net,
+            tax,
+            total: net + tax.map(|(_, tax)| tax).unwrap_or_default(),
+        }
+    }
+    /// Write invoice about the given jobs in the configured format.
+    pub fn write<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        jobs: &JobList,
+        context: &Context,
+    ) -> Result<(), Error> {
+        let properties = jobs.configuration.get_checked(&self.tags)?;
+        let items = self.items(jobs);
+        let sums = Self::sums(&items, properties);
+        let date = context.time().format("%Y-%m-%d");
+        match self.format {
+            OutputFormat::Text | OutputFormat::Markdown => {
+                self.write_markdown(w, &items, &sums, properties, &date)
+            }
+            OutputFormat::Html => self.write_html(w, &items, &sums, properties, &date),
+        }
+    }
+    /// Write invoice as Markdown.
+    fn write_markdown<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        items: &[Item],
+        sums: &Sums,
+        properties: &Properties,
+        date: &str,
+    ) -> Result<(), Error> {
+        let currency = properties.currency.unwrap_or(DEFAULT_CURRENCY);
+        writeln!(w, "# Invoice {}", self.number)?;
+        writeln!(w)?;
+        writeln!(w, "Date: {date}")?;
+        writeln!(w)?;
+        if let Some(client) = &properties.client {
+            writeln!(w, "{}", client.lines().map(escape_markdown).join("  \n"))?;
+            writeln!(w)?;
+        }
+        if self.grouping == Grouping::Day {
+            writeln!(w, "| Date | Description | Hours | Amount |")?;
+            writeln!(w, "|---|---|--:|--:|")?;
+        } else {
+            writeln!(w, "| Description | Hours | Amount |")?;
+            writeln!(w, "|---|--:|--:|")?;
+        }
+        for item in items {
+            if let Some(date) = &item.date {
+                write!(w, "| {date} ")?;
+            }
+            writeln!(
+                w,
+                "| {} | {} | {} |",
+                escape_markdown(&item.description),
+                item.hours,
+                money(item.amount, currency)
+            )?;
+        }
+        writeln!(w)?;
+        writeln!(w, "Hours: {}  ", sums.hours)?;
+        writeln!(w, "Net: {}  ", money(sums.net, currency))?;
+        if let Some((rate, tax)) = sums.tax {
+            writeln!(w, "Tax ({rate}%): {}  ", money(tax, currency))?;
+        }
+        writeln!(w, "**Total: {}**", money(sums.total, currency))?;
+        Ok(())
+    }
+    /// Write invoice as standalone HTML document.
+    fn write_html<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        items: &[Item],
+        sums: &Sums,
+        properties: &Properties,
+        date: &str,
+    ) -> Result<(), Error> {
+        let currency = properties.currency.unwrap_or(DEFAULT_CURRENCY);
+        let title = format!("Invoice {}", self.number);
+        html_begin(w, &title)?;
+        writeln!(w, "<h1>{title}</h1>")?;
+        writeln!(w, "<p>Date: {date}</p>")?;
+        if let Some(client) = &properties.client {
+            writeln!(
+                w,
+                "<address>{}</address>",
+                client.lines().map(escape_html).join("<br>")
+            )?;
+        }
+        let columns = if self.grouping == Grouping::Day {
+            vec!["Date", "Description", "Hours", "Amount"]
+        } else {
+            vec!["Description", "Hours", "Amount"]
+        };
+        writeln!(w, "<table>")?;
+        writeln!(w, "<thead>")?;
+        writeln!(
+            w,
+            "<tr>{}</tr>",
+            columns
+                .iter()
+                .map(|name| format!("<th>{name}</th>"))
+                .join("")
+        )?;
+        writeln!(w, "</thead>")?;
+        writeln!(w, "<tbody>")?;
+        for item in items {
+            write!(w, "<tr>")?;
+            if let Some(date) = &item.date {
+                write!(w, "<td>{date}</td>")?;
+            }
+            writeln!(
+                w,
+                "<td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+                escape_html(&item.description),
+                item.hours,
+                money(item.amount, currency)
+            )?;
+        }
+        writeln!(w, "</tbody>")?;
+        writeln!(w, "<tfoot>")?;
+        let span = columns.len() - 1;
+        writeln!(
+            w,
+            "<tr><td colspan=\"{}\">Net</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+            span - 1,
+            sums.hours,
+            money(sums.net, currency)
+        )?;
+        if let Some((rate, tax)) = sums.tax {
+            writeln!(
+                w,
+                "<tr><td colspan=\"{span}\">Tax ({rate}%)</td><td class=\"number\">{}</td></tr>",
+                money(tax, currency)
+            )?;
+        }
+        writeln!(
+            w,
+            "<tr><td colspan=\"{span}\">Total</td><td class=\"number\">{}</td></tr>",
+            money(sums.total, currency)
+        )?;
+        writeln!(w, "</tfoot>")?;
+        writeln!(w, "</table>")?;
+        html_end(w)
+    }
+}
+
+/// Test summarizing jobs into items and formatting of money.
+#[test]
+fn test_items() {
+    let configuration = Configuration {
+        base: Properties {
+            rate: Some(20.0),
+            tax: Some(19.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let jobs = [
+        Job::new(
+            "2023-2-1 8:00".into(),
+            Some("2023-2-1 9:00".into()),
+            Some("coding".into()),
+            None,
+        )
+        .unwrap(),
+        Job::new(
+            "2023-2-1 10:00".into(),
+            Some("2023-2-1 10:30".into()),
+            Some("meeting".into()),
+            None,
+        )
+        .unwrap(),
+        Job::new(
+            "2023-2-2 8:00".into(),
+            Some("2023-2-2 9:00".into()),
+            Some("coding".into()),
+            None,
+        )
+        .unwrap(),
+    ];
+    let list = JobList::new(jobs.iter().enumerate().collect(), &configuration);
+    let mut invoice = Invoice {
+        number: 1,
+        tags: TagSet::new(),
+        positions: list.positions(),
+        grouping: Grouping::Day,
+        format: OutputFormat::Markdown,
+    };
+    let items = invoice.items(&list);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].description, "coding; meeting");
+    assert_eq!(items[0].hours, 1.5);
+
+    invoice.grouping = Grouping::Message;
+    let items = invoice.items(&list);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].description, "coding");
+    assert_eq!(items[0].amount, 40.0);
+
+    let sums = Invoice::sums(&items, &configuration.base);
+    assert_eq!(money(sums.total, DEFAULT_CURRENCY), "$59.50");
+    assert_eq!(money(123456.785, DEFAULT_CURRENCY), "$123,456.79");
+    assert_eq!(money(-0.5, '€'), "-€0.50");
+}
@@ -4,14 +4,11 @@ use super::prelude::*;
 use chrono::{Days, NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
 
-/// default for `deleted` in `Job`
-fn none<T>() -> Option<T> {
-    Option::None
-}
-
 /// One portion of work
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Job {
+    /// Unique identifier
+    pub id: JobId,
     /// Starting time
     pub start: DateTime,
     /// Ending time or None if not finished yet)
@@ -21,8 +18,11 @@ pub struct Job {
     /// List of tags
     pub tags: TagSet,
     /// Deletion Mark
-    #[serde(default = "none")]
     deleted: Option<DateTime>,
+    /// Number of the invoice by which this job was billed
+    invoice: Option<u32>,
+    /// Lock which protects job from modification and deletion
+    locked: bool,
 }
 
 impl Job {
@@ -39,15 +39,14 @@ impl Job {
             }
         }
         Ok(Self {
+            id: JobId::new(),
             start,
             end,
             message,
-            tags: if let Some(tags) = tags {
-                tags
-            } else {
-                TagSet::new()
-            },
+            tags: tags.unwrap_or_default(),
             deleted: None,
+            invoice: None,
+            locked: false,
         })
     }
     /// Return `true` if latest job has no ending.
@@ -62,8 +61,40 @@ impl Job {
     pub fn is_deleted(&self) -> bool {
         self.deleted.is_some()
     }
+    /// Return date and time of deletion if job has been deleted.
+    pub fn deleted(&self) -> Option<DateTime> {
+        self.deleted
+    }
+    /// Restore this job by removing the deletion mark.
+    pub fn undelete(&mut self) {
+        self.deleted = None;
+    }
+    /// Set deletion mark (e.g. when loading job from a storage).
+    pub fn set_deleted(&mut self, deleted: Option<DateTime>) {
+        self.deleted = deleted;
+    }
+    /// Return number of the invoice if job has been billed.
+    pub fn invoice(&self) -> Option<u32> {
+        self.invoice
+    }
+    /// Return `true` if job has been billed.
+    pub fn is_billed(&self) -> bool {
+        self.invoice.is_some()
+    }
+    /// Set number of the invoice by which this job was billed.
+    pub fn set_invoice(&mut self, invoice: Option<u32>) {
+        self.invoice = invoice;
+    }
+    /// Return `true` if job is protected from modification and deletion.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    /// Lock (`true`) or unlock (`false`) job.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
     /// Get minutes worked without rounding to resolution.
-    fn minutes(&self) -> i64 {
+    pub fn minutes(&self) -> i64 {
         let end = if let Some(end) = self.end {
             end
         } else {
@@ -119,32 +150,40 @@ impl Job {
                 .and_time(NaiveTime::from_num_seconds_from_midnight_opt(0, 0).unwrap());
             if e > end {
                 result.push(Job {
+                    id: self.id.clone(),
                     start: DateTime::from_local(&start),
                     end: Some(DateTime::from_local(&end)),
                     message: self.message.clone(),
                     tags: self.tags.clone(),
                     deleted: None,
+                    invoice: self.invoice,
+                    locked: self.locked,
                 });
                 break;
             }
 
             result.push(Job {
+                id: self.id.clone(),
                 start: DateTime::from_local(&start),
                 end: Some(DateTime::from_local(&e)),
                 message: self.message.clone(),
                 tags: self.tags.clone(),
                 deleted: None,
+                invoice: self.invoice,
+                locked: self.locked,
             });
             start = e;
         }
         result
     }
-    /// Print a job in human readable format using colors.
+    /// Print a job in human readable format using colors and highlight matches of `searches` in the message.
     pub fn writeln(
         &self,
         f: &mut std::fmt::Formatter<'_>,
         properties: &Properties,
+        searches: &[Search],
     ) -> std::fmt::Result {
+        writeln!(f, "     ID: {}", self.id)?;
         writeln!(f, "  Start: {}", format::start(&self.start))?;
         if let Some(end) = &self.end {
             writeln!(f, "    End: {}", format::end(end))?;
@@ -162,24 +201,33 @@ impl Job {
             writeln!(f, "  Costs: {}", format::pay(hours, properties))?;
         }
         if let Some(message) = &self.message {
-            writeln!(f, "Message: {}", format::message(message, 9))?;
+            writeln!(f, "Message: {}", format::message(message, 9, searches))?;
         }
         if !self.tags.is_empty() {
             writeln!(f, "   Tags: {}", self.tags)?;
         }
+        if let Some(invoice) = &self.invoice {
+            writeln!(f, "Invoice: {}", invoice)?;
+        }
+        if self.locked {
+            writeln!(f, " Locked: yes")?;
+        }
+        if let Some(deleted) = &self.deleted {
+            writeln!(f, "Deleted: {}", deleted)?;
+        }
         Ok(())
     }
 }
 
 impl std::fmt::Display for Job {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.writeln(f, &Properties::default())
+        self.writeln(f, &Properties::default(), &[])
     }
 }
 
 impl PartialOrd for Job {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.start.partial_cmp(&other.start)
+        Some(self.cmp(other))
     }
 }
 
@@ -0,0 +1,104 @@
+//! Unique identifiers of jobs.
+
+use super::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier of a job which (other than its position) never changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct JobId(String);
+
+impl JobId {
+    /// Generate a new random identifier.
+    pub fn new() -> Self {
+        Self(format!("{:016x}", rand::random::<u64>()))
+    }
+    /// Parse an identifier which must consist of 16 hexadecimal digits.
+    pub fn parse(id: &str) -> Option<Self> {
+        if id.len() == 16 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some(Self::from(id))
+        } else {
+            None
+        }
+    }
+    /// Return `true` if identifier starts with the given `prefix`.
+    pub fn matches(&self, prefix: &str) -> bool {
+        self.0.starts_with(&prefix.to_lowercase())
+    }
+    /// Return identifier as string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for JobId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for JobId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for JobId {
+    fn from(id: &str) -> Self {
+        Self(id.to_lowercase())
+    }
+}
+
+/// Reference to a single job by position or by (a unique prefix of) its identifier.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JobRef {
+    /// Position within the database.
+    Position(usize),
+    /// Identifier or unique prefix of it.
+    Id(String),
+}
+
+impl JobRef {
+    /// Parse a position (starting at `1`) or an identifier prefixed with `@`.
+    pub fn parse(job: &str) -> Result<Self, Error> {
+        if let Some(id) = job.strip_prefix('@') {
+            if !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Ok(Self::Id(id.to_lowercase()));
+            }
+        } else if let Ok(pos) = job.parse::<usize>() {
+            if pos > 0 {
+                return Ok(Self::Position(pos - 1));
+            }
+        }
+        Err(Error::JobRefFormat(job.into()))
+    }
+}
+
+impl std::fmt::Display for JobRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Position(pos) => write!(f, "{}", pos + 1),
+            Self::Id(id) => write!(f, "@{id}"),
+        }
+    }
+}
+
+/// Test parsing of job references.
+#[test]
+fn test_job_ref() {
+    assert_eq!(JobRef::parse("3").unwrap(), JobRef::Position(2));
+    assert_eq!(
+        JobRef::parse("@3F2a").unwrap(),
+        JobRef::Id("3f2a".to_string())
+    );
+    assert!(JobRef::parse("0").is_err());
+    assert!(JobRef::parse("@").is_err());
+    assert!(JobRef::parse("@xyz").is_err());
+    assert!(JobId::from("3f2a9c1b4e5d6a7f").matches("3F2A"));
+    assert_eq!(
+        JobId::parse("3F2A9C1B4E5D6A7F"),
+        Some(JobId::from("3f2a9c1b4e5d6a7f"))
+    );
+    assert_eq!(JobId::parse("3f2a"), None);
+    assert_eq!(JobId::parse("3f2a9c1b4e5d6a7g"), None);
+}
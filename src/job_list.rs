@@ -12,6 +12,8 @@ pub struct JobList<'a> {
     jobs: Vec<IndexedJob<'a>>,
     /// Copy of the configuration of the original [Jobs] database.
     pub configuration: &'a Configuration,
+    /// Searches whose matches get highlighted within the messages when displayed.
+    searches: Vec<Search>,
 }
 
 impl<'a> IntoIterator for JobList<'a> {
@@ -28,6 +30,7 @@ impl<'a> From<&'a JobListOwned> for JobList<'a> {
         Self {
             configuration: &list.configuration,
             jobs: list.iter().map(|(n, j)| (*n, j)).collect(),
+            searches: Vec::new(),
         }
     }
 }
@@ -38,13 +41,17 @@ impl<'a> std::fmt::Display for JobList<'a> {
         let mut count = 0;
         for (pos, job) in self.iter() {
             writeln!(f, "    Pos: {}", pos + 1)?;
-            job.writeln(f, self.configuration.get(&job.tags))?;
+            job.writeln(f, self.configuration.get(&job.tags), &self.searches)?;
             writeln!(f)?;
             count += 1;
         }
         let pay = {
             if let Some(pay) = self.pay_overall() {
-                format!(" = ${}", format::pay_pure(pay))
+                format!(
+                    " = {}{}",
+                    self.configuration.base.currency.unwrap_or(DEFAULT_CURRENCY),
+                    format::pay_pure(pay)
+                )
             } else {
                 String::new()
             }
@@ -68,6 +75,7 @@ impl<'a> JobList<'a> {
         Self {
             jobs,
             configuration,
+            searches: Vec::new(),
         }
     }
     /// Create job list on base of the given database but does not copy the jobs themselves (but it's configuration).
@@ -75,14 +83,20 @@ impl<'a> JobList<'a> {
         Self {
             jobs: Vec::new(),
             configuration: &jobs.configuration,
+            searches: Vec::new(),
         }
     }
+    /// Highlight matches of the given searches within the messages when displayed.
+    pub fn highlight(mut self, searches: Vec<Search>) -> Self {
+        self.searches = searches;
+        self
+    }
     /// Add a new job.
     pub fn push(&mut self, pos: usize, job: &'a Job) {
         self.jobs.push((pos, job))
     }
     /// Get read-only iterator over included jobs.
-    pub fn iter(&self) -> core::slice::Iter<'_, IndexedJob> {
+    pub fn iter(&self) -> core::slice::Iter<'_, IndexedJob<'_>> {
         self.jobs.iter()
     }
     /// Return `true` if list is empty.
@@ -1,12 +1,8 @@
 //! *Jobber*'s database.
 
 use super::prelude::*;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, BufWriter},
-};
+use std::{collections::BTreeSet, path::Path};
 
 /// serializable instance of the *jobber* database
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -18,6 +14,23 @@ pub struct Jobs {
     jobs: Vec<Job>,
     /// Database configuration.
     pub configuration: Configuration,
+    /// Journal of applied operations for undo and redo.
+    journal: Journal,
+    /// Number of jobs which already are persistent within the storage.
+    #[serde(skip)]
+    stored: usize,
+    /// Positions of persistent jobs which were changed in memory.
+    #[serde(skip)]
+    changed: BTreeSet<usize>,
+    /// Flag that is `true` if persistent jobs were removed in memory.
+    #[serde(skip)]
+    removed: bool,
+    /// Schema version the database was upgraded from when it was loaded.
+    #[serde(skip)]
+    upgraded: Option<u32>,
+    /// Maximum number of operations kept in the journal (`None` for [JOURNAL_LIMIT]).
+    #[serde(skip)]
+    journal_limit: Option<usize>,
 }
 
 impl IntoIterator for Jobs {
@@ -37,16 +50,6 @@ impl std::ops::Index<usize> for Jobs {
     }
 }
 
-/// Adds a version number to the database when serializing.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Versioned<T> {
-    /// Version number string.
-    version: String,
-    /// Jobber database.
-    #[serde(flatten)]
-    jobs: T,
-}
-
 impl Jobs {
     /// Create an empty jobber database
     pub fn new() -> Self {
@@ -54,7 +57,48 @@ impl Jobs {
             modified: false,
             jobs: Vec::new(),
             configuration: Default::default(),
+            journal: Journal::default(),
+            stored: 0,
+            changed: BTreeSet::new(),
+            removed: false,
+            upgraded: None,
+            journal_limit: None,
+        }
+    }
+    /// Create database from loaded parts.
+    pub fn from_parts(jobs: Vec<Job>, configuration: Configuration, journal: Journal) -> Self {
+        Self {
+            jobs,
+            configuration,
+            journal,
+            ..Self::new()
         }
+        .loaded()
+    }
+    /// Mark all jobs as persistent within the storage after loading.
+    pub fn loaded(mut self) -> Self {
+        self.stored = self.jobs.len();
+        tags::init(&self);
+        self
+    }
+    /// Remember that the database was upgraded from an older `schema` version when loading.
+    pub fn upgraded_from(mut self, schema: u32) -> Self {
+        if schema < schema::SCHEMA {
+            self.upgraded = Some(schema);
+        }
+        self
+    }
+    /// Get schema version the database was upgraded from when loading (`None` if it was up to date).
+    pub fn upgraded(&self) -> Option<u32> {
+        self.upgraded
+    }
+    /// Set maximum number of operations kept in the journal.
+    pub fn set_journal_limit(&mut self, limit: usize) {
+        self.journal_limit = Some(limit);
+    }
+    /// Get journal of applied operations.
+    pub fn journal(&self) -> &Journal {
+        &self.journal
     }
     // public version of push fpr testing
     #[cfg(test)]
@@ -114,12 +158,12 @@ impl Jobs {
         Ok(operation)
     }
     /// Get a list of all jobs in database
-    pub fn all(&self) -> JobList {
+    pub fn all(&self) -> JobList<'_> {
         let result: Vec<IndexedJob> = self.iter().enumerate().collect();
         JobList::new(result, &self.configuration)
     }
     /// Generate a list of some jobs.
-    pub fn list(&self, positions: &Positions) -> JobList {
+    pub fn list(&self, positions: &Positions) -> JobList<'_> {
         let result: Vec<IndexedJob> = self
             .iter()
             .enumerate()
@@ -137,16 +181,48 @@ impl Jobs {
     }
     // public version of filter fpr testing
     #[cfg(test)]
-    pub fn _filter(&self, range: &Range, tags: &TagSet) -> Result<JobList, Error> {
+    pub fn _filter(&self, range: &Range, tags: &TagSet) -> Result<JobList<'_>, Error> {
         self.filter(range, tags)
     }
     /// Filter jobs by range and tags and return a job list with the result.
     /// Deleted jobs will be omitted.
-    fn filter(&self, range: &Range, tags: &TagSet) -> Result<JobList, Error> {
+    fn filter(&self, range: &Range, tags: &TagSet) -> Result<JobList<'_>, Error> {
+        self.select(range, tags, false)
+    }
+    /// Filter deleted jobs by range and tags and return a job list with the result.
+    fn filter_deleted(&self, range: &Range, tags: &TagSet) -> Result<JobList<'_>, Error> {
+        self.select(range, tags, true)
+    }
+    /// Find position of the job a reference points to.
+    fn resolve(&self, job: &JobRef) -> Result<usize, Error> {
+        match job {
+            JobRef::Position(pos) => Ok(*pos),
+            JobRef::Id(id) => self.find_id(id),
+        }
+    }
+    /// Find position of the only job whose identifier starts with `prefix`.
+    pub fn find_id(&self, prefix: &str) -> Result<usize, Error> {
+        let found: Vec<usize> = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.id.matches(prefix))
+            .map(|(pos, _)| pos)
+            .collect();
+        match found.len() {
+            0 => Err(Error::JobIdNotFound(prefix.into())),
+            1 => Ok(found[0]),
+            n => Err(Error::AmbiguousJobId(prefix.into(), n)),
+        }
+    }
+    /// Filter jobs by range and tags and return a job list with the result.
+    /// Only deleted jobs will be selected if `deleted` is `true` and only undeleted ones if not.
+    fn select(&self, range: &Range, tags: &TagSet, deleted: bool) -> Result<JobList<'_>, Error> {
         let mut jobs = JobList::new_from(self);
+        let range = self.resolve_range(range, deleted)?;
         for (n, job) in self.jobs.iter().enumerate() {
-            // sort out any deleted jobs
-            if job.is_deleted() {
+            // sort out any deleted (or undeleted) jobs
+            if job.is_deleted() != deleted {
                 continue;
             }
             let mut tag_ok = true;
@@ -157,48 +233,50 @@ impl Jobs {
                 };
             }
 
-            let range_ok = match range {
-                Range::None => false,
-                Range::All => true,
-                Range::Count(_) => true,
-                Range::At(pos) => pos.contains(&n),
-                Range::PositionRange(f, t) => n >= *f && n <= *t,
-                Range::FromPosition(p) => n >= *p,
-                Range::Day(d) => {
-                    job.start.date() <= *d
-                        && if let Some(end) = job.end {
-                            end.date() >= *d
-                        } else {
-                            true
-                        }
-                }
-                Range::TimeRange(f, t) => {
-                    job.start < *t
-                        && if let Some(end) = job.end {
-                            end >= *f
-                        } else {
-                            true
-                        }
-                }
-                Range::Since(s) => {
-                    job.start >= *s
-                        || if let Some(end) = job.end {
-                            end >= *s
-                        } else {
-                            true
-                        }
-                }
-            };
-
-            if tag_ok && range_ok {
+            if tag_ok && range.contains(n, job) {
                 jobs.push(n, job);
             }
         }
         if let Range::Count(c) = range {
-            jobs.drain(*c)?;
+            jobs.drain(c)?;
         }
         Ok(jobs)
     }
+    /// Replace ranges which depend on other jobs (IDs and counts within filters) by positions.
+    fn resolve_range(&self, range: &Range, deleted: bool) -> Result<Range, Error> {
+        Ok(match range {
+            // find positions of referenced IDs
+            Range::Ids(ids) => Range::At(
+                ids.iter()
+                    .map(|id| self.find_id(id))
+                    .collect::<Result<Vec<usize>, Error>>()?,
+            ),
+            Range::Filter(filter) => {
+                // tags in use or configured
+                let mut known = self.tags();
+                for tag in self.configuration.tags.keys() {
+                    known.insert(tag);
+                }
+                Range::Filter(Box::new(filter.resolve(
+                    &|range| {
+                        if let Range::Count(_) = range {
+                            Ok(Range::At(
+                                self.select(range, &TagSet::new(), deleted)?
+                                    .positions()
+                                    .iter()
+                                    .copied()
+                                    .collect(),
+                            ))
+                        } else {
+                            self.resolve_range(range, deleted)
+                        }
+                    },
+                    &known,
+                )?))
+            }
+            range => range.clone(),
+        })
+    }
     /// copy message from last jobs or ask user to enter one.
     fn copy_last_or_enter_message(
         &self,
@@ -312,12 +390,27 @@ impl Jobs {
                     return Err(Error::NoOpenJob);
                 }
             }
-            Command::List { range, tags } => Operation::List(
+            Command::List {
+                range,
+                tags,
+                format,
+            } => Operation::List(
                 self.filter(&range, &tags.clone().into())?.positions(),
                 range,
                 tags,
+                format,
             ),
-            Command::Report { range, tags } => Operation::Report(
+            Command::Report {
+                range,
+                tags,
+                format,
+            } => Operation::Report(
+                self.filter(&range, &tags.clone().into())?.positions(),
+                range,
+                tags,
+                format,
+            ),
+            Command::Balance { range, tags } => Operation::Balance(
                 self.filter(&range, &tags.clone().into())?.positions(),
                 range,
                 tags,
@@ -326,6 +419,7 @@ impl Jobs {
                 range,
                 tags,
                 columns,
+                dialect,
             } => {
                 let tags = tags.into();
                 Operation::ExportCSV(
@@ -333,11 +427,68 @@ impl Jobs {
                     range,
                     Some(tags),
                     Columns::from(columns),
+                    dialect,
                 )
             }
+            Command::ExportJSON { range, tags } => Operation::ExportJSON(
+                self.filter(&range, &tags.clone().into())?.positions(),
+                range,
+                tags,
+            ),
+            Command::ExportICS { range, tags } => Operation::ExportICS(
+                self.filter(&range, &tags.clone().into())?.positions(),
+                range,
+                tags,
+            ),
+            Command::ExportTimewarrior { range, tags } => Operation::ExportTimewarrior(
+                self.filter(&range, &tags.clone().into())?.positions(),
+                range,
+                tags,
+            ),
+            Command::ExportTimeclock { range, tags } => Operation::ExportTimeclock(
+                self.filter(&range, &tags.clone().into())?.positions(),
+                range,
+                tags,
+            ),
             Command::ShowConfiguration => Operation::ShowConfiguration(self.configuration.clone()),
             Command::SetConfiguration { tags, update } => Operation::Configure(tags, update),
-            Command::LegacyImport { filename } => Operation::Import(filename, 0, TagSet::new()),
+            Command::Import { filename, format } => {
+                let jobs = import(&filename, &format)?;
+                // imported IDs must be unique
+                let mut errors: Vec<String> = jobs
+                    .iter()
+                    .filter(|job| self.jobs.iter().any(|j| j.id == job.id))
+                    .map(|job| format!("job with ID '{}' already exists", job.id))
+                    .collect();
+                // and must not appear twice within the imported file
+                errors.extend(
+                    jobs.iter()
+                        .enumerate()
+                        .filter(|(n, job)| jobs[..*n].iter().any(|j| j.id == job.id))
+                        .map(|(_, job)| format!("job with ID '{}' is imported twice", job.id)),
+                );
+                // only one imported job may be left open
+                errors.extend(
+                    jobs.iter()
+                        .filter(|job| job.is_open())
+                        .skip(1)
+                        .map(|job| format!("job starting {} is open like another one", job.start)),
+                );
+                if !errors.is_empty() {
+                    return Err(Error::Import(filename, errors));
+                }
+                let tags = self.tags();
+                let mut new_tags = TagSet::new();
+                for job in &jobs {
+                    new_tags.insert_many(job.tags.filter(|tag| !tags.contains(tag)));
+                }
+                Operation::Import(filename, jobs, new_tags)
+            }
+            Command::Merge { filename } => Operation::Merge(Merge::new(
+                &filename,
+                self,
+                crate::storage::open(&filename, 0)?.load()?,
+            )),
             Command::ListTags { range, tags } => {
                 Operation::ListTags(self.filter(&range, &tags.into())?.tags())
             }
@@ -350,7 +501,7 @@ impl Jobs {
             } => {
                 // use given pos or the last undeleted job
                 let pos = if let Some(pos) = pos {
-                    pos
+                    self.resolve(&pos)?
                 } else if let Some(position) = self.last_position() {
                     position
                 } else {
@@ -387,11 +538,98 @@ impl Jobs {
                     return Err(Error::JobNotFound(pos));
                 }
             }
+            Command::Invoice {
+                range,
+                tags,
+                grouping,
+                format,
+            } => {
+                // bill finished jobs only once
+                let mut positions = Vec::new();
+                for (pos, job) in self.filter(&range, &tags)?.iter() {
+                    if job.end.is_some() && !job.is_billed() {
+                        if self.configuration.get_checked(&job.tags)?.rate.is_none() {
+                            return Err(Error::MissingRate(pos + 1));
+                        }
+                        positions.push(*pos);
+                    }
+                }
+                if positions.is_empty() {
+                    return Err(Error::NothingToInvoice);
+                }
+                // continue with the highest number used so far unless the configuration demands a higher one
+                let number = self
+                    .jobs
+                    .iter()
+                    .filter_map(|job| job.invoice())
+                    .max()
+                    .map_or(1, |number| number + 1)
+                    .max(self.configuration.get_checked(&tags)?.invoice.unwrap_or(1));
+                Operation::Invoice(Invoice {
+                    number,
+                    tags,
+                    positions: Positions::from_iter(positions),
+                    grouping,
+                    format,
+                })
+            }
             Command::Delete { range, tags } => {
                 Operation::Delete(self.filter(&range, &tags.into())?.positions())
             }
+            Command::ListDeleted { range, tags } => Operation::ListDeleted(
+                self.filter_deleted(&range, &tags.clone().into())?
+                    .positions(),
+                range,
+                tags,
+            ),
+            Command::Undelete { range, tags } => {
+                let positions = self.filter_deleted(&range, &tags.into())?.positions();
+                if positions.is_empty() {
+                    return Err(Error::NothingToUndelete);
+                }
+                Operation::Undelete(positions)
+            }
+            Command::Lock { range, tags } => Operation::Lock(Positions::from_iter(
+                self.filter(&range, &tags.into())?
+                    .iter()
+                    .filter(|(_, job)| job.end.is_some() && !job.is_locked())
+                    .map(|(pos, _)| *pos),
+            )),
+            Command::Unlock { range, tags } => Operation::Unlock(Positions::from_iter(
+                self.filter(&range, &tags.into())?
+                    .iter()
+                    .filter(|(_, job)| job.is_locked())
+                    .map(|(pos, _)| *pos),
+            )),
+            Command::Purge { before } => {
+                let mut purged = Vec::new();
+                let mut moved = Vec::new();
+                for (pos, job) in self.jobs.iter().enumerate() {
+                    let purge = match (job.deleted(), before) {
+                        (Some(deleted), Some(before)) => deleted < before,
+                        (Some(_), None) => true,
+                        (None, _) => false,
+                    };
+                    if purge {
+                        purged.push(pos);
+                    } else if !purged.is_empty() {
+                        moved.push((pos, pos - purged.len()));
+                    }
+                }
+                Operation::Purge(Positions::from_iter(purged), moved)
+            }
+            Command::Output { filename, command } => {
+                Operation::Output(filename, Box::new(self.interpret(&command)?))
+            }
+            Command::Undo { count } => Operation::Undo(self.journal.to_undo(count)?),
+            Command::Redo { count } => Operation::Redo(self.journal.to_redo(count)?),
         })
     }
+    /// Record an entry into the journal which keeps as many entries as configured.
+    fn record(&mut self, entry: Entry) {
+        self.journal
+            .record(entry, self.journal_limit.unwrap_or(JOURNAL_LIMIT));
+    }
     /// get start date of the first job (which is not deleted)
     fn first_date(&self) -> Option<Date> {
         self.first().map(|first| first.start.date())
@@ -436,9 +674,22 @@ impl Jobs {
                     // add new job to database
                     self.push(job.clone());
                     self.modified = true;
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: format!("Added job at position {}", *position + 1),
+                        changes: vec![Change::Job {
+                            pos: *position,
+                            before: None,
+                            after: Some(job.clone()),
+                        }],
+                    });
                 }
             }
             Operation::Modify(pos, job) => {
+                // locked jobs must be unlocked first
+                if self.jobs[*pos].is_locked() {
+                    return Err(Error::JobsLocked(Positions::from_iter([*pos])));
+                }
                 // check job consistency
                 checks.check(self, Some(*pos), job, context)?;
                 // finished jobs need message
@@ -446,11 +697,31 @@ impl Jobs {
                     return Err(Error::EnterMessage);
                 } else {
                     // overwrite job in database
-                    self.jobs[*pos] = job.clone();
+                    let before = std::mem::replace(&mut self.jobs[*pos], job.clone());
+                    self.changed.insert(*pos);
                     self.modified = true;
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: format!("Modified job at position {}", *pos + 1),
+                        changes: vec![Change::Job {
+                            pos: *pos,
+                            before: Some(before),
+                            after: Some(job.clone()),
+                        }],
+                    });
                 }
             }
             Operation::Delete(positions) => {
+                // locked jobs must be unlocked first
+                let locked = Positions::from_iter(
+                    positions
+                        .iter()
+                        .filter(|pos| self.jobs[**pos].is_locked())
+                        .cloned(),
+                );
+                if !locked.is_empty() {
+                    return Err(Error::JobsLocked(locked));
+                }
                 // maybe confirm deletion
                 if checks.has(Check::ConfirmDeletion) {
                     return Err(Error::Warnings(vec![Warning::ConfirmDeletion(
@@ -458,34 +729,340 @@ impl Jobs {
                     )]));
                 } else {
                     // delete job(s) at given position(s)
+                    let mut changes = Vec::new();
                     for pos in positions.iter() {
+                        let before = self.jobs[*pos].clone();
                         self.jobs[*pos].delete(context);
+                        self.changed.insert(*pos);
                         self.modified = true;
+                        changes.push(Change::Job {
+                            pos: *pos,
+                            before: Some(before),
+                            after: Some(self.jobs[*pos].clone()),
+                        });
+                    }
+                    if !changes.is_empty() {
+                        self.record(Entry {
+                            time: context.time(),
+                            summary: format!(
+                                "Deleted job(s) at position(s) {}",
+                                positions.into_ranges()
+                            ),
+                            changes,
+                        });
                     }
                 }
             }
-            Operation::Import(filename, count, new_tags) => {
-                (*count, *new_tags) = self.legacy_import(filename)?;
-                self.modified = *count > 0;
+            Operation::Merge(merge) => {
+                // do not merge an open job if there is already one
+                if merge.jobs.iter().any(|job| job.is_open()) {
+                    self.check_finished()?;
+                }
+                // check merged jobs for overlapping with ours and with each other
+                if checks.has(Check::Overlaps) {
+                    let mut warnings = Vec::new();
+                    let mut merged = self.clone();
+                    for job in &merge.jobs {
+                        match Checks::no_confirm().check(&merged, None, job, context) {
+                            Err(Error::Warnings(mut w)) => warnings.append(&mut w),
+                            Err(err) => return Err(err),
+                            Ok(()) => (),
+                        }
+                        merged.jobs.push(job.clone());
+                    }
+                    if !warnings.is_empty() {
+                        return Err(Error::Warnings(warnings));
+                    }
+                }
+                let mut changes = Vec::new();
+                for job in &merge.jobs {
+                    changes.push(Change::Job {
+                        pos: self.jobs.len(),
+                        before: None,
+                        after: Some(job.clone()),
+                    });
+                    self.push(job.clone());
+                }
+                if self.configuration != merge.configuration {
+                    changes.push(Change::Configuration {
+                        before: self.configuration.clone(),
+                        after: merge.configuration.clone(),
+                    });
+                    self.configuration = merge.configuration.clone();
+                }
+                if !changes.is_empty() {
+                    self.modified = true;
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: format!(
+                            "Merged {} jobs from {}",
+                            merge.jobs.len(),
+                            merge.filename
+                        ),
+                        changes,
+                    });
+                }
+            }
+            Operation::Import(filename, jobs, new_tags) => {
+                // do not import an open job if there is already one
+                if jobs.iter().any(|job| job.is_open()) {
+                    self.check_finished()?;
+                }
+                let mut warnings = Vec::new();
+                // check imported jobs for overlapping
+                if checks.has(Check::Overlaps) {
+                    for job in jobs.iter() {
+                        match Checks::no_confirm().check(self, None, job, context) {
+                            Err(Error::Warnings(mut w)) => warnings.append(&mut w),
+                            Err(err) => return Err(err),
+                            Ok(()) => (),
+                        }
+                    }
+                }
+                if checks.has(Check::UnknownTags) && !new_tags.is_empty() {
+                    warnings.push(Warning::UnknownTags(new_tags.clone()));
+                }
+                if !warnings.is_empty() {
+                    return Err(Error::Warnings(warnings));
+                }
+                let first = self.jobs.len();
+                let count = jobs.len();
+                for job in jobs.iter() {
+                    self.push(job.clone());
+                }
+                if count > 0 {
+                    self.modified = true;
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: format!("Imported {count} jobs from {filename}"),
+                        changes: (first..self.jobs.len())
+                            .map(|pos| Change::Job {
+                                pos,
+                                before: None,
+                                after: Some(self.jobs[pos].clone()),
+                            })
+                            .collect(),
+                    });
+                }
             }
             Operation::Configure(tags, update) => {
+                let before = self.configuration.clone();
                 self.modified = self.configuration.set(tags, update);
+                if self.modified {
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: if let Some(tags) = tags {
+                            format!("Changed configuration of tag(s) {}", tags.0.join(","))
+                        } else {
+                            "Changed base configuration".to_string()
+                        },
+                        changes: vec![Change::Configuration {
+                            before,
+                            after: self.configuration.clone(),
+                        }],
+                    });
+                }
+            }
+            Operation::Invoice(invoice) => {
+                // mark job(s) as billed
+                let mut changes = Vec::new();
+                for pos in invoice.positions.iter() {
+                    let before = self.jobs[*pos].clone();
+                    self.jobs[*pos].set_invoice(Some(invoice.number));
+                    self.jobs[*pos].set_locked(true);
+                    self.changed.insert(*pos);
+                    self.modified = true;
+                    changes.push(Change::Job {
+                        pos: *pos,
+                        before: Some(before),
+                        after: Some(self.jobs[*pos].clone()),
+                    });
+                }
+                self.record(Entry {
+                    time: context.time(),
+                    summary: format!(
+                        "Billed job(s) at position(s) {} by invoice {}",
+                        invoice.positions.into_ranges(),
+                        invoice.number
+                    ),
+                    changes,
+                });
+                invoice.write(w, &self.list(&invoice.positions), context)?;
+            }
+            Operation::Lock(positions) => self.lock(positions, true, context),
+            Operation::Unlock(positions) => self.lock(positions, false, context),
+            Operation::Undelete(positions) => {
+                // do not restore an open job if there is already one
+                if positions.iter().any(|pos| self.jobs[*pos].end.is_none()) {
+                    self.check_finished()?;
+                }
+                // restore job(s) at given position(s)
+                let mut changes = Vec::new();
+                for pos in positions.iter() {
+                    let before = self.jobs[*pos].clone();
+                    self.jobs[*pos].undelete();
+                    self.changed.insert(*pos);
+                    self.modified = true;
+                    changes.push(Change::Job {
+                        pos: *pos,
+                        before: Some(before),
+                        after: Some(self.jobs[*pos].clone()),
+                    });
+                }
+                if !changes.is_empty() {
+                    self.record(Entry {
+                        time: context.time(),
+                        summary: format!(
+                            "Restored job(s) at position(s) {}",
+                            positions.into_ranges()
+                        ),
+                        changes,
+                    });
+                }
             }
-            Operation::List(positions, _, _) => {
-                write!(w, "{}", self.list(positions))?;
+            Operation::Purge(purged, _) if !purged.is_empty() => {
+                // maybe confirm purge
+                if checks.has(Check::ConfirmPurge) {
+                    return Err(Error::Warnings(vec![Warning::ConfirmPurge(purged.clone())]));
+                }
+                // remove jobs from database
+                let mut pos = 0;
+                self.jobs.retain(|_| {
+                    pos += 1;
+                    !purged.contains(&(pos - 1))
+                });
+                // forget purged jobs within journal
+                self.journal.purge(purged);
+                self.removed = true;
+                tags::init(self);
+                self.modified = true;
             }
-            Operation::Report(positions, _, _) => report(w, &self.list(positions), context)?,
-            Operation::ExportCSV(positions, _, _, columns) => {
-                export_csv(w, &self.list(positions), columns, context)?
+            Operation::Undo(entries) => {
+                // revert changes of every entry in reverse order
+                for entry in entries.iter() {
+                    for change in entry.changes.iter().rev() {
+                        self.apply(change, true);
+                    }
+                }
+                self.journal.undo(entries.len());
+                tags::init(self);
+                self.modified = true;
+            }
+            Operation::Redo(entries) => {
+                // re-apply changes of every entry in original order
+                for entry in entries.iter() {
+                    for change in entry.changes.iter() {
+                        self.apply(change, false);
+                    }
+                }
+                self.journal.redo(entries.len());
+                tags::init(self);
+                self.modified = true;
+            }
+            Operation::List(positions, range, _, format) => match format {
+                OutputFormat::Text => {
+                    write!(w, "{}", self.list(positions).highlight(range.searches()))?
+                }
+                OutputFormat::Markdown => list_markdown(w, &self.list(positions))?,
+                OutputFormat::Html => list_html(w, &self.list(positions))?,
+            },
+            Operation::ListDeleted(positions, range, _) => {
+                write!(w, "{}", self.list(positions).highlight(range.searches()))?;
+            }
+            Operation::Report(positions, _, _, format) => match format {
+                OutputFormat::Text => report(w, &self.list(positions), context)?,
+                OutputFormat::Markdown => report_markdown(w, &self.list(positions), context)?,
+                OutputFormat::Html => report_html(w, &self.list(positions), context)?,
+            },
+            Operation::Balance(positions, range, tags) => {
+                balance(w, &self.list(positions), range, tags, context)?
+            }
+            Operation::ExportCSV(positions, _, _, columns, dialect) => {
+                export_csv(w, &self.list(positions), columns, dialect, context)?
+            }
+            Operation::ExportJSON(positions, _, _) => {
+                export_json(w, &self.list(positions), context)?
+            }
+            Operation::ExportICS(positions, _, _) => export_ics(w, &self.list(positions), context)?,
+            Operation::Output(filename, operation) => {
+                if checks.has(Check::OutputFileExists) && Path::new(filename).exists() {
+                    return Err(Error::OutputFileExists(filename.clone()));
+                }
+                // keep any existing file if the operation fails
+                let mut output = Vec::new();
+                self.operate(&mut output, operation, checks, context)?;
+                std::fs::write(filename.as_str(), format::unstyled(&output))?;
+            }
+            Operation::ExportTimewarrior(positions, _, _) => {
+                export_timewarrior(w, &self.list(positions))?
+            }
+            Operation::ExportTimeclock(positions, _, _) => {
+                export_timeclock(w, &self.list(positions))?
             }
             _ => (),
         }
         Ok(())
     }
+    /// Lock (if `lock` is `true`) or unlock jobs at the given positions.
+    fn lock(&mut self, positions: &Positions, lock: bool, context: &Context) {
+        let mut changes = Vec::new();
+        for pos in positions.iter() {
+            let before = self.jobs[*pos].clone();
+            self.jobs[*pos].set_locked(lock);
+            self.changed.insert(*pos);
+            self.modified = true;
+            changes.push(Change::Job {
+                pos: *pos,
+                before: Some(before),
+                after: Some(self.jobs[*pos].clone()),
+            });
+        }
+        if !changes.is_empty() {
+            self.record(Entry {
+                time: context.time(),
+                summary: format!(
+                    "{} job(s) at position(s) {}",
+                    if lock { "Locked" } else { "Unlocked" },
+                    positions.into_ranges()
+                ),
+                changes,
+            });
+        }
+    }
+    /// Apply the state before (if `undo` is `true`) or after the given change to the database.
+    ///
+    /// Locked jobs are not protected here because locking is journaled too: changes are applied in (reverse)
+    /// order, so a job's lock gets undone before any earlier change of that job is reverted.
+    fn apply(&mut self, change: &Change, undo: bool) {
+        match change {
+            Change::Job { pos, before, after } => {
+                match if undo { before } else { after } {
+                    Some(job) => {
+                        if *pos < self.jobs.len() {
+                            self.jobs[*pos] = job.clone();
+                            self.changed.insert(*pos);
+                        } else {
+                            self.jobs.push(job.clone());
+                        }
+                    }
+                    // job did not exist in that state
+                    None => {
+                        self.jobs.truncate(*pos);
+                        if *pos < self.stored {
+                            self.removed = true;
+                        }
+                    }
+                }
+            }
+            Change::Configuration { before, after } => {
+                self.configuration = if undo { before } else { after }.clone();
+            }
+        }
+    }
     /// Check if there is an open job in the database.
     fn check_finished(&self) -> Result<(), Error> {
         if let Some((pos, job)) = self.get_open_with_pos() {
-            return Err(Error::OpenJob(pos, job.clone()));
+            return Err(Error::OpenJob(pos, Box::new(job.clone())));
         }
         Ok(())
     }
@@ -511,35 +1088,37 @@ impl Jobs {
         }
         None
     }
-    /// Load database from file
+    /// Load database from JSON file.
     pub fn load(filename: &str) -> Result<Jobs, Error> {
-        let file = File::options()
-            .read(true)
-            .open(filename)
-            .map_err(Error::Io)?;
-        let reader = BufReader::new(file);
-        let versioned = serde_json::from_reader::<_, Versioned<Jobs>>(reader)
-            .map_err(Error::Json)?;
-        tags::init(&versioned.jobs);
-        Ok(versioned.jobs)
-    }
-    /// Save database into file.
-    pub fn save(&mut self, filename: &str) -> Result<(), Error> {
-        let file = File::options()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(filename)
-            .unwrap();
-        let writer = BufWriter::new(file);
-        let versioned_jobs = Versioned {
-            version: clap::crate_version!().to_string(),
-            jobs: &self,
-        };
-        // pretty print when running tests
-        serde_json::to_writer_pretty(writer, &versioned_jobs).map_err(Error::Json)?;
-
+        JsonStorage::new(filename, 0).load()
+    }
+    /// Save database into JSON file (see [JsonStorage]).
+    pub fn save(&mut self, filename: &str, backups: usize) -> Result<(), Error> {
+        self.store(&mut JsonStorage::new(filename, backups))
+    }
+    /// Write modifications into the given storage.
+    ///
+    /// Depending on what has changed since loading the storage gets updated partially or rewritten completely.
+    pub fn store(&mut self, storage: &mut dyn Storage) -> Result<(), Error> {
+        let appended = self.jobs.len() > self.stored;
+        let changed = Positions::from_iter(
+            self.changed
+                .iter()
+                .filter(|pos| **pos < self.stored.min(self.jobs.len()))
+                .cloned(),
+        );
+        if self.removed || (appended && !changed.is_empty()) {
+            storage.save(self)?;
+        } else if appended {
+            storage.append(self, self.stored)?;
+        } else {
+            storage.update(self, &changed)?;
+        }
+        self.stored = self.jobs.len();
+        self.changed.clear();
+        self.removed = false;
         self.modified = false;
+        self.journal.stored();
         Ok(())
     }
     /// Write all jobs into formatter.
@@ -553,51 +1132,10 @@ impl Jobs {
                 continue;
             }
             writeln!(f, "\n    Pos: {}", n + 1)?;
-            job.writeln(f, self.configuration.get(&job.tags))?;
+            job.writeln(f, self.configuration.get(&job.tags), &[])?;
         }
         Ok(())
     }
-    /// Import legacy jobber database from CSV.
-    fn legacy_import(&mut self, filename: &str) -> Result<(usize, TagSet), Error> {
-        let file = File::options()
-            .read(true)
-            .open(filename)
-            .map_err(Error::Io)?;
-        let reader = BufReader::new(file);
-        let tags = self.tags();
-        let mut count = 0;
-        let mut new_tags = TagSet::new();
-        for line in reader.lines() {
-            let re = Regex::new(r#""(.*)";"(.*)";"(.*)";"(.*)"$"#).unwrap();
-            for cap in re.captures_iter(&line.unwrap()) {
-                let start = DateTime::from_rfc3339(&cap[1])?;
-                let end = cap[2].to_string();
-                let end = if end.is_empty() {
-                    None
-                } else {
-                    Some(DateTime::from_rfc3339(&end)?)
-                };
-                let message = cap[3].to_string();
-                let message = if message.is_empty() {
-                    None
-                } else {
-                    Some(message)
-                };
-                let tags = cap[4].to_string();
-                let tags = if tags.is_empty() {
-                    None
-                } else {
-                    let tags: TagSet = tags.as_str().into();
-                    new_tags.insert_many(tags.clone());
-                    Some(tags)
-                };
-                self.push(Job::new(start, end, message, tags).unwrap());
-                self.modified = true;
-                count += 1;
-            }
-        }
-        Ok((count, new_tags.filter(|t| tags.contains(t))))
-    }
     /// Print info about the database.
     fn intro(&self, context: &Context) -> Result<(), Error> {
         eprintln!("\nWelcome to jobber!\n");
@@ -683,3 +1221,11 @@ impl std::fmt::Display for Jobs {
         self.writeln(f, |_, _| true)
     }
 }
+
+/// Test loading a database of an older schema version.
+#[test]
+fn test_load_migrated() {
+    let jobs = Jobs::load("database.json").unwrap();
+    assert_eq!(jobs.count(), 3);
+    assert_eq!(jobs.configuration.base.rate, Some(100.0));
+}
@@ -0,0 +1,160 @@
+//! Journal of all modifications which were applied to a *jobber* database.
+//!
+//! The journal is stored within the database and allows to undo and redo operations.
+
+use super::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of operations kept within the journal if not configured otherwise.
+pub const JOURNAL_LIMIT: usize = 100;
+
+/// Single change within the database.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Change {
+    /// Job at position `pos` changed from `before` into `after` (`None` if job did not exist).
+    Job {
+        pos: usize,
+        before: Option<Job>,
+        after: Option<Job>,
+    },
+    /// Database configuration changed from `before` into `after`.
+    Configuration {
+        before: Configuration,
+        after: Configuration,
+    },
+}
+
+/// All changes of one applied operation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    /// Time when the operation was applied.
+    pub time: DateTime,
+    /// Short description of the operation.
+    pub summary: String,
+    /// Changes in the order they were applied.
+    pub changes: Vec<Change>,
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.time, self.summary)
+    }
+}
+
+/// Journal of applied operations.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Journal {
+    /// Applied (and maybe undone) operations in chronological order.
+    entries: Vec<Entry>,
+    /// Number of entries at the end of `entries` which have been undone and can be redone.
+    undone: usize,
+    /// Sequence number of the first entry (numbers of dropped entries will not be reused).
+    #[serde(skip)]
+    first: usize,
+    /// Sequence number of the first entry which was recorded or changed since last storing.
+    #[serde(skip)]
+    unstored: usize,
+}
+
+impl Journal {
+    /// Create journal from stored entries of which the first one has the sequence number `first`.
+    pub fn restore(first: usize, entries: Vec<Entry>, undone: usize) -> Self {
+        Self {
+            unstored: first + entries.len(),
+            entries,
+            undone,
+            first,
+        }
+    }
+    /// Record a new entry and drop any undone entries which then can not be redone anymore.
+    ///
+    /// The oldest entries get dropped so that at most `limit` entries remain.
+    pub fn record(&mut self, entry: Entry, limit: usize) {
+        self.entries.truncate(self.entries.len() - self.undone);
+        self.undone = 0;
+        self.unstored = self.unstored.min(self.first + self.entries.len());
+        self.entries.push(entry);
+        let dropped = self.entries.len().saturating_sub(limit);
+        self.entries.drain(..dropped);
+        self.first += dropped;
+    }
+    /// Number of entries which can be undone.
+    pub fn undoable(&self) -> usize {
+        self.entries.len() - self.undone
+    }
+    /// Number of entries which can be redone.
+    pub fn redoable(&self) -> usize {
+        self.undone
+    }
+    /// Get the last `count` entries which can be undone (latest first).
+    pub fn to_undo(&self, count: usize) -> Result<Vec<Entry>, Error> {
+        if count == 0 || self.undoable() == 0 {
+            return Err(Error::NothingToUndo);
+        }
+        let end = self.undoable();
+        let start = end - count.min(end);
+        Ok(self.entries[start..end].iter().rev().cloned().collect())
+    }
+    /// Get the next `count` entries which can be redone (earliest first).
+    pub fn to_redo(&self, count: usize) -> Result<Vec<Entry>, Error> {
+        if count == 0 || self.redoable() == 0 {
+            return Err(Error::NothingToRedo);
+        }
+        let start = self.undoable();
+        let end = start + count.min(self.undone);
+        Ok(self.entries[start..end].to_vec())
+    }
+    /// Remove all changes of the given `purged` positions and renumber the positions of the remaining changes.
+    ///
+    /// Entries which have no changes left get dropped.
+    pub fn purge(&mut self, purged: &Positions) {
+        self.unstored = self.first;
+        let undoable = self.undoable();
+        let mut n = 0;
+        self.entries.retain_mut(|entry| {
+            entry.changes.retain_mut(|change| match change {
+                Change::Job { pos, .. } if purged.contains(pos) => false,
+                Change::Job { pos, .. } => {
+                    *pos -= purged.iter().filter(|purged| *purged < pos).count();
+                    true
+                }
+                Change::Configuration { .. } => true,
+            });
+            n += 1;
+            if entry.changes.is_empty() {
+                if n > undoable {
+                    self.undone -= 1;
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
+    /// Mark `count` entries as undone.
+    pub fn undo(&mut self, count: usize) {
+        self.undone += count;
+    }
+    /// Mark `count` undone entries as redone.
+    pub fn redo(&mut self, count: usize) {
+        self.undone -= count;
+    }
+    /// Sequence number of the first entry.
+    pub fn first(&self) -> usize {
+        self.first
+    }
+    /// Sequence number of the first entry which needs to be stored.
+    pub fn unstored(&self) -> usize {
+        self.unstored
+    }
+    /// Get entries which were recorded or changed since last storing together with their sequence numbers.
+    pub fn unstored_entries(&self) -> impl Iterator<Item = (usize, &Entry)> {
+        (self.first..)
+            .zip(self.entries.iter())
+            .skip(self.unstored.saturating_sub(self.first))
+    }
+    /// Mark all entries as stored.
+    pub fn stored(&mut self) {
+        self.unstored = self.first + self.entries.len();
+    }
+}
@@ -10,23 +10,43 @@ pub mod date_time;
 pub mod duration;
 pub mod error;
 pub mod export;
+pub mod filter;
 pub mod format;
+pub mod ics;
+pub mod import;
+pub mod invoice;
 pub mod job;
+pub mod job_id;
 pub mod job_list;
 pub mod jobs;
+pub mod journal;
+pub mod lock;
+pub mod markup;
+pub mod merge;
 pub mod operation;
 pub mod partial_date_time;
 pub mod positions;
 pub mod range;
 pub mod reports;
+pub mod schema;
+pub mod search;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod storage;
 pub mod tag_set;
 pub mod tags;
+pub mod timeclock;
+pub mod timewarrior;
 
 /// What *jobber* needs.
 pub mod prelude {
+    #[cfg(feature = "sqlite")]
+    pub use super::sqlite::*;
     pub use super::{
         check::*, command::*, configuration::*, context::*, date_time::*, duration::*, error::*,
-        export::*, format, job::*, job_list::*, jobs::*, operation::*, partial_date_time::*,
-        positions::*, range::*, reports::*, tag_set::*, tags,
+        export::*, filter::*, format, ics::*, import::*, invoice::*, job::*, job_id::*,
+        job_list::*, jobs::*, journal::*, lock::*, markup::*, merge::*, operation::*,
+        partial_date_time::*, positions::*, range::*, reports::*, schema, search::*, storage::*,
+        tag_set::*, tags, timeclock::*, timewarrior::*,
     };
 }
@@ -0,0 +1,64 @@
+//! Advisory locking of a *jobber* database file.
+//!
+//! Prevents concurrent *jobber* processes from overwriting each others changes.
+
+use super::prelude::*;
+use std::fs::{File, TryLockError};
+
+/// Exclusive lock on a database file which is released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+    /// Opened lock file (`<database>.lock`).
+    _file: File,
+}
+
+impl Lock {
+    /// Lock the database file with the given `filename`.
+    ///
+    /// Because the database file gets replaced when saving, a separate lock file `<filename>.lock` is used.
+    /// The lock file is intentionally left in place after the lock has been released because removing it would
+    /// race with processes which already opened it and wait for the lock.
+    /// If the database is already locked by another process this returns [Error::DatabaseBusy] or
+    /// blocks until the lock gets released if `wait` is `true`.
+    pub fn acquire(filename: &str, wait: bool) -> Result<Self, Error> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(format!("{filename}.lock"))
+            .map_err(Error::Io)?;
+        if wait {
+            file.lock().map_err(Error::Io)?;
+        } else {
+            match file.try_lock() {
+                Ok(()) => (),
+                Err(TryLockError::WouldBlock) => {
+                    return Err(Error::DatabaseBusy(filename.to_string()))
+                }
+                Err(TryLockError::Error(err)) => return Err(Error::Io(err)),
+            }
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// Test exclusive locking.
+#[test]
+fn test_lock() {
+    let dir = std::env::temp_dir().join(format!("jobber-test-lock-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let filename = dir.join("jobber.json").to_string_lossy().to_string();
+
+    let lock = Lock::acquire(&filename, false).unwrap();
+    // second lock fails while first is held
+    assert!(matches!(
+        Lock::acquire(&filename, false),
+        Err(Error::DatabaseBusy(_))
+    ));
+    drop(lock);
+    // lock can be acquired again after release
+    assert!(Lock::acquire(&filename, false).is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,334 @@
+//! Markdown and HTML output of job lists and calendar reports.
+
+use super::prelude::*;
+use itertools::Itertools;
+use separator::Separatable;
+
+/// Format in which lists and reports are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Terminal text (which may include colors)
+    #[default]
+    Text,
+    /// Markdown tables
+    Markdown,
+    /// Standalone HTML document
+    Html,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Markdown => write!(f, "Markdown"),
+            OutputFormat::Html => write!(f, "HTML"),
+        }
+    }
+}
+
+/// Style sheet of HTML documents.
+const STYLE: &str = "table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; vertical-align: top; }
+td.number { text-align: right; }
+td.exceeded { color: #c60; font-weight: bold; }
+tfoot { font-weight: bold; }";
+
+/// Escape text to be used within a Markdown table cell.
+pub fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('|', "\\|")
+        .replace('*', "\\*")
+        .replace('_', "\\_")
+        .replace('\n', "<br>")
+}
+
+/// Escape text to be used within HTML.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Format payment with a leading currency sign.
+fn pay(pay: f64, currency: char) -> String {
+    format!("{currency}{}", pay.separated_string())
+}
+
+/// Currency sign of sums over jobs of possibly different tags (taken from the base configuration).
+fn currency(jobs: &JobList) -> char {
+    jobs.configuration.base.currency.unwrap_or(DEFAULT_CURRENCY)
+}
+
+/// Format total line which ends lists and reports.
+fn total(jobs: &JobList) -> String {
+    let pay = if let Some(pay) = jobs.pay_overall() {
+        format!(" = {}", self::pay(pay, currency(jobs)))
+    } else {
+        String::new()
+    };
+    format!(
+        "{} job(s), {} hours{}",
+        jobs.len(),
+        jobs.hours_overall(),
+        pay
+    )
+}
+
+/// Title of a month within a report like `2/2023`.
+fn month_title(month: &CalendarMonth) -> String {
+    format!("{}/{}", month.month, month.year)
+}
+
+/// Hours and pay summary of a month within a report like `Feb 2023: 1.5 hours = $30`.
+fn month_summary(month: &CalendarMonth, currency: char) -> String {
+    let pay = if let Some(pay) = month.pay {
+        format!(" = {}", self::pay(pay, currency))
+    } else {
+        String::new()
+    };
+    format!(
+        "{} {}: {} hours{}",
+        MONTHS[month.month as usize - 1],
+        month.year,
+        month.hours,
+        pay
+    )
+}
+
+/// Columns of a job within a list (pay column only if any rate is configured).
+fn list_rows(jobs: &JobList) -> (bool, Vec<Vec<String>>) {
+    let with_pay = jobs.pay_overall().is_some();
+    let rows = jobs
+        .iter()
+        .map(|(pos, job)| {
+            let properties = jobs.get_configuration(&job.tags);
+            let hours = job.hours(properties);
+            let mut row = vec![
+                (pos + 1).to_string(),
+                job.id.to_string(),
+                job.start.to_string(),
+                job.end.map(|end| end.to_string()).unwrap_or_default(),
+                hours.to_string(),
+            ];
+            if with_pay {
+                row.push(
+                    properties
+                        .rate
+                        .map(|rate| {
+                            pay(
+                                rate * hours,
+                                properties.currency.unwrap_or(DEFAULT_CURRENCY),
+                            )
+                        })
+                        .unwrap_or_default(),
+                );
+            }
+            row.push(job.message.clone().unwrap_or_default());
+            row.push(job.tags.0.join(", "));
+            row
+        })
+        .collect();
+    (with_pay, rows)
+}
+
+/// Names of the columns of a list.
+fn list_header(with_pay: bool) -> Vec<&'static str> {
+    let mut header = vec!["Pos", "ID", "Start", "End", "Hours"];
+    if with_pay {
+        header.push("Pay");
+    }
+    header.extend(["Message", "Tags"]);
+    header
+}
+
+/// Write jobs as Markdown table followed by a total line.
+pub fn list_markdown<W: std::io::Write>(w: &mut W, jobs: &JobList) -> Result<(), Error> {
+    let (with_pay, rows) = list_rows(jobs);
+    let header = list_header(with_pay);
+    writeln!(w, "| {} |", header.join(" | "))?;
+    writeln!(w, "|{}", "---|".repeat(header.len()))?;
+    for row in rows {
+        writeln!(
+            w,
+            "| {} |",
+            row.iter().map(|cell| escape_markdown(cell)).join(" | ")
+        )?;
+    }
+    writeln!(w)?;
+    writeln!(w, "**Total:** {}", total(jobs))?;
+    Ok(())
+}
+
+/// Write jobs as standalone HTML document with a table which ends with a total line.
+pub fn list_html<W: std::io::Write>(w: &mut W, jobs: &JobList) -> Result<(), Error> {
+    let (with_pay, rows) = list_rows(jobs);
+    let header = list_header(with_pay);
+    html_begin(w, "Jobs")?;
+    writeln!(w, "<table>")?;
+    writeln!(w, "<thead>")?;
+    writeln!(
+        w,
+        "<tr>{}</tr>",
+        header
+            .iter()
+            .map(|name| format!("<th>{name}</th>"))
+            .join("")
+    )?;
+    writeln!(w, "</thead>")?;
+    writeln!(w, "<tbody>")?;
+    for row in rows {
+        write!(w, "<tr>")?;
+        for (name, cell) in header.iter().zip(row) {
+            if matches!(*name, "Pos" | "Hours" | "Pay") {
+                write!(w, "<td class=\"number\">{}</td>", escape_html(&cell))?;
+            } else {
+                write!(w, "<td>{}</td>", escape_html(&cell).replace('\n', "<br>"))?;
+            }
+        }
+        writeln!(w, "</tr>")?;
+    }
+    writeln!(w, "</tbody>")?;
+    writeln!(w, "<tfoot>")?;
+    writeln!(
+        w,
+        "<tr><td colspan=\"{}\">Total: {}</td></tr>",
+        header.len(),
+        escape_html(&total(jobs))
+    )?;
+    writeln!(w, "</tfoot>")?;
+    writeln!(w, "</table>")?;
+    html_end(w)
+}
+
+/// Write calendar report as Markdown with one table per month.
+pub fn report_markdown<W: std::io::Write>(
+    w: &mut W,
+    jobs: &JobList,
+    context: &Context,
+) -> Result<(), Error> {
+    for month in calendar(jobs, context) {
+        writeln!(w, "## {}", month_title(&month))?;
+        writeln!(w)?;
+        writeln!(w, "| Day | {} | Week |", WEEKDAYS.join(" | "))?;
+        writeln!(w, "|--:|{}--:|", "--:|".repeat(WEEKDAYS.len()))?;
+        for week in &month.weeks {
+            write!(
+                w,
+                "| {} |",
+                week.label
+                    .map(|label| label.to_string())
+                    .unwrap_or_default()
+            )?;
+            for day in &week.days {
+                match day {
+                    Some(Some(day)) if day.exceeded || day.hours > 24.0 => {
+                        write!(w, " **{}** |", day.hours)?
+                    }
+                    Some(Some(day)) => write!(w, " {} |", day.hours)?,
+                    Some(None) => write!(w, " - |")?,
+                    None => write!(w, "  |")?,
+                }
+            }
+            writeln!(w, " {} |", week.hours)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "{}", month_summary(&month, currency(jobs)))?;
+        writeln!(w)?;
+    }
+    writeln!(w, "**Total:** {}", total(jobs))?;
+    Ok(())
+}
+
+/// Write calendar report as standalone HTML document with one table per month.
+pub fn report_html<W: std::io::Write>(
+    w: &mut W,
+    jobs: &JobList,
+    context: &Context,
+) -> Result<(), Error> {
+    html_begin(w, "Report")?;
+    for month in calendar(jobs, context) {
+        writeln!(w, "<h2>{}</h2>", month_title(&month))?;
+        writeln!(w, "<table>")?;
+        writeln!(w, "<thead>")?;
+        writeln!(
+            w,
+            "<tr><th>Day</th>{}<th>Week</th></tr>",
+            WEEKDAYS
+                .iter()
+                .map(|day| format!("<th>{day}</th>"))
+                .join("")
+        )?;
+        writeln!(w, "</thead>")?;
+        writeln!(w, "<tbody>")?;
+        for week in &month.weeks {
+            write!(
+                w,
+                "<tr><td class=\"number\">{}</td>",
+                week.label
+                    .map(|label| label.to_string())
+                    .unwrap_or_default()
+            )?;
+            for day in &week.days {
+                match day {
+                    Some(Some(day)) if day.exceeded || day.hours > 24.0 => {
+                        write!(w, "<td class=\"number exceeded\">{}</td>", day.hours)?
+                    }
+                    Some(Some(day)) => write!(w, "<td class=\"number\">{}</td>", day.hours)?,
+                    Some(None) => write!(w, "<td class=\"number\">-</td>")?,
+                    None => write!(w, "<td></td>")?,
+                }
+            }
+            writeln!(w, "<td class=\"number\">{}</td></tr>", week.hours)?;
+        }
+        writeln!(w, "</tbody>")?;
+        writeln!(w, "<tfoot>")?;
+        writeln!(
+            w,
+            "<tr><td colspan=\"{}\">{}</td></tr>",
+            WEEKDAYS.len() + 2,
+            escape_html(&month_summary(&month, currency(jobs)))
+        )?;
+        writeln!(w, "</tfoot>")?;
+        writeln!(w, "</table>")?;
+    }
+    writeln!(
+        w,
+        "<p><strong>Total:</strong> {}</p>",
+        escape_html(&total(jobs))
+    )?;
+    html_end(w)
+}
+
+/// Write beginning of a standalone HTML document until the opened body.
+pub fn html_begin<W: std::io::Write>(w: &mut W, title: &str) -> Result<(), Error> {
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html>")?;
+    writeln!(w, "<head>")?;
+    writeln!(w, "<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>{title}</title>")?;
+    writeln!(w, "<style>\n{STYLE}\n</style>")?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    Ok(())
+}
+
+/// Write end of a standalone HTML document.
+pub fn html_end<W: std::io::Write>(w: &mut W) -> Result<(), Error> {
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")?;
+    Ok(())
+}
+
+/// Test escaping of Markdown and HTML special characters.
+#[test]
+fn test_escape() {
+    assert_eq!(escape_markdown("a|b\nc_d"), "a\\|b<br>c\\_d");
+    assert_eq!(escape_markdown("<b>fish</b>"), "&lt;b&gt;fish&lt;/b&gt;");
+    assert_eq!(
+        escape_html("<b>\"fish\" & chips</b>"),
+        "&lt;b&gt;&quot;fish&quot; &amp; chips&lt;/b&gt;"
+    );
+}
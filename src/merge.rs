@@ -0,0 +1,204 @@
+//! Merging of two *jobber* databases.
+
+use super::prelude::*;
+
+/// Property of a tag configuration which has different values in both databases.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Tag whose configuration conflicts (`None` for base configuration).
+    pub tag: Option<String>,
+    /// Name of the property.
+    pub property: &'static str,
+    /// Value within this database (which will be kept).
+    pub ours: String,
+    /// Value within the other database.
+    pub theirs: String,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tag) = &self.tag {
+            write!(f, "tag {tag}")?;
+        } else {
+            write!(f, "base")?;
+        }
+        write!(
+            f,
+            ": {} is {} here but {} in other database (kept {})",
+            self.property, self.ours, self.theirs, self.ours
+        )
+    }
+}
+
+/// Result of comparing another database with this one.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// File name of the other database.
+    pub filename: String,
+    /// Jobs of the other database which are missing in this one.
+    pub jobs: Vec<Job>,
+    /// Number of jobs which already exist in this database.
+    pub duplicates: usize,
+    /// Positions of jobs in this database which have the same ID as a different job in the other database.
+    pub diverged: Positions,
+    /// Combined configuration of both databases.
+    pub configuration: Configuration,
+    /// Conflicting properties which could not be combined.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Compare `theirs` (loaded from `filename`) with `ours` to find out what must be merged.
+    ///
+    /// Jobs which have the same ID or the same content as an existing job will be skipped.
+    /// Deleted jobs will not be merged.
+    pub fn new(filename: &str, ours: &Jobs, theirs: Jobs) -> Self {
+        let mut jobs = Vec::new();
+        let mut duplicates = 0;
+        let mut diverged = Vec::new();
+        for job in theirs.iter() {
+            if let Some(pos) = ours.iter().position(|our| our.id == job.id) {
+                if !Self::same(&ours[pos], job) {
+                    diverged.push(pos);
+                }
+                duplicates += 1;
+            } else if ours.iter().any(|our| Self::same(our, job)) {
+                duplicates += 1;
+            } else if !job.is_deleted() {
+                jobs.push(job.clone());
+            }
+        }
+        let mut configuration = ours.configuration.clone();
+        let conflicts = Self::combine(&mut configuration, &theirs.configuration);
+        Self {
+            filename: filename.into(),
+            jobs,
+            duplicates,
+            diverged: Positions::from_iter(diverged),
+            configuration,
+            conflicts,
+        }
+    }
+    /// Return `true` if both jobs have the same content (not regarding their IDs).
+    fn same(ours: &Job, theirs: &Job) -> bool {
+        ours.start == theirs.start
+            && ours.end == theirs.end
+            && ours.message == theirs.message
+            && ours.tags == theirs.tags
+            && ours.is_deleted() == theirs.is_deleted()
+    }
+    /// Add missing tag configurations and properties of `theirs` into `ours` and return all conflicts.
+    fn combine(ours: &mut Configuration, theirs: &Configuration) -> Vec<Conflict> {
+        let mut conflicts = Self::combine_properties(None, &mut ours.base, &theirs.base);
+        for (tag, properties) in &theirs.tags {
+            if let Some(our_properties) = ours.tags.get_mut(tag) {
+                conflicts.append(&mut Self::combine_properties(
+                    Some(tag),
+                    our_properties,
+                    properties,
+                ));
+            } else {
+                ours.tags.insert(tag.clone(), properties.clone());
+            }
+        }
+        conflicts
+    }
+    /// Fill missing properties in `ours` from `theirs` and return all conflicting properties.
+    fn combine_properties(
+        tag: Option<&String>,
+        ours: &mut Properties,
+        theirs: &Properties,
+    ) -> Vec<Conflict> {
+        fn combine<T: PartialEq + Clone + ToString>(
+            conflicts: &mut Vec<Conflict>,
+            tag: Option<&String>,
+            property: &'static str,
+            ours: &mut Option<T>,
+            theirs: &Option<T>,
+        ) {
+            match (&ours, theirs) {
+                (None, Some(_)) => *ours = theirs.clone(),
+                (Some(our), Some(their)) if our != their => conflicts.push(Conflict {
+                    tag: tag.cloned(),
+                    property,
+                    ours: our.to_string(),
+                    theirs: their.to_string(),
+                }),
+                _ => (),
+            }
+        }
+        let mut conflicts = Vec::new();
+        combine(
+            &mut conflicts,
+            tag,
+            "resolution",
+            &mut ours.resolution,
+            &theirs.resolution,
+        );
+        combine(&mut conflicts, tag, "rate", &mut ours.rate, &theirs.rate);
+        combine(
+            &mut conflicts,
+            tag,
+            "max_hours",
+            &mut ours.max_hours,
+            &theirs.max_hours,
+        );
+        combine(
+            &mut conflicts,
+            tag,
+            "client",
+            &mut ours.client,
+            &theirs.client,
+        );
+        combine(&mut conflicts, tag, "tax", &mut ours.tax, &theirs.tax);
+        combine(
+            &mut conflicts,
+            tag,
+            "currency",
+            &mut ours.currency,
+            &theirs.currency,
+        );
+        combine(
+            &mut conflicts,
+            tag,
+            "invoice",
+            &mut ours.invoice,
+            &theirs.invoice,
+        );
+        combine(
+            &mut conflicts,
+            tag,
+            "target",
+            &mut ours.target,
+            &theirs.target,
+        );
+        conflicts
+    }
+}
+
+impl std::fmt::Display for Merge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Merged {} job(s) from {} (skipped {} existing job(s)).",
+            self.jobs.len(),
+            self.filename,
+            self.duplicates
+        )?;
+        if !self.diverged.is_empty() {
+            write!(
+                f,
+                "\nJob(s) at position(s) {} differ from the ones with the same ID in {} and were kept.",
+                self.diverged.into_ranges(),
+                self.filename
+            )?;
+        }
+        if !self.conflicts.is_empty() {
+            write!(f, "\nConfiguration conflicts:")?;
+            for conflict in &self.conflicts {
+                write!(f, "\n  {conflict}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -18,20 +18,50 @@ pub enum Operation {
     Modify(usize, Job),
     /// Remove jobs from
     Delete(Positions),
-    /// Import file
-    Import(String, usize, TagSet),
+    /// Import jobs from file and add new tags.
+    Import(String, Vec<Job>, TagSet),
+    /// Merge jobs and configuration of another database.
+    Merge(Merge),
     /// Change configuration
     Configure(Option<TagSet>, Properties),
     /// List jobs
-    List(Positions, Range, Option<TagSet>),
+    List(Positions, Range, Option<TagSet>, OutputFormat),
     /// Report jobs
-    Report(Positions, Range, Option<TagSet>),
+    Report(Positions, Range, Option<TagSet>, OutputFormat),
+    /// Report flextime balance
+    Balance(Positions, Range, Option<TagSet>),
     /// Export jobs
-    ExportCSV(Positions, Range, Option<TagSet>, Columns),
+    ExportCSV(Positions, Range, Option<TagSet>, Columns, CsvDialect),
+    /// Export jobs as JSON
+    ExportJSON(Positions, Range, Option<TagSet>),
+    /// Export jobs as iCalendar
+    ExportICS(Positions, Range, Option<TagSet>),
+    /// Export jobs as Timewarrior data
+    ExportTimewarrior(Positions, Range, Option<TagSet>),
+    /// Export jobs as timeclock
+    ExportTimeclock(Positions, Range, Option<TagSet>),
+    /// Bill jobs by an invoice
+    Invoice(Invoice),
     /// List all available tags.
     ListTags(TagSet),
     /// Show the database configuration.
     ShowConfiguration(Configuration),
+    /// List deleted jobs
+    ListDeleted(Positions, Range, Option<TagSet>),
+    /// Restore deleted jobs
+    Undelete(Positions),
+    /// Lock jobs
+    Lock(Positions),
+    /// Unlock jobs
+    Unlock(Positions),
+    /// Permanently remove deleted jobs at the given positions and move remaining jobs from old to new positions.
+    Purge(Positions, Vec<(usize, usize)>),
+    /// Write output of another operation into a file.
+    Output(String, Box<Operation>),
+    /// Undo journal entries (latest first).
+    Undo(Vec<Entry>),
+    /// Redo journal entries (earliest first).
+    Redo(Vec<Entry>),
 }
 
 impl Operation {
@@ -70,7 +100,87 @@ impl std::fmt::Display for Operation {
                     positions.into_ranges()
                 )
             }
-            Operation::Import(filename, count, new_tags) => {
+            Operation::Invoice(invoice) => {
+                write!(
+                    f,
+                    "Billed {} job(s) with tags {} by invoice {}:",
+                    invoice.positions.len(),
+                    invoice.tags,
+                    invoice.number
+                )
+            }
+            Operation::Undelete(positions) => {
+                write!(
+                    f,
+                    "Restoring job(s) at position(s): {}",
+                    positions.into_ranges()
+                )
+            }
+            Operation::Lock(positions) => {
+                if positions.is_empty() {
+                    return write!(f, "There are no unlocked jobs to lock.");
+                }
+                write!(
+                    f,
+                    "Locked {} job(s) at position(s): {}",
+                    positions.len(),
+                    positions.into_ranges()
+                )
+            }
+            Operation::Unlock(positions) => {
+                if positions.is_empty() {
+                    return write!(f, "There are no locked jobs to unlock.");
+                }
+                write!(
+                    f,
+                    "Unlocked {} job(s) at position(s): {}",
+                    positions.len(),
+                    positions.into_ranges()
+                )
+            }
+            Operation::Purge(purged, moved) => {
+                if purged.is_empty() {
+                    return write!(f, "There are no deleted jobs to purge.");
+                }
+                write!(
+                    f,
+                    "Purged {} deleted job(s) at position(s): {}",
+                    purged.len(),
+                    purged.into_ranges()
+                )?;
+                if !moved.is_empty() {
+                    writeln!(f, "\n\nRenumbered remaining jobs:\n")?;
+                    // combine consecutive moves by the same offset into ranges
+                    let mut ranges: Vec<(usize, usize, usize)> = Vec::new();
+                    for (old, new) in moved {
+                        match ranges.last_mut() {
+                            Some((from, to, new_from))
+                                if *to + 1 == *old && *new_from + (*old - *from) == *new =>
+                            {
+                                *to = *old
+                            }
+                            _ => ranges.push((*old, *old, *new)),
+                        }
+                    }
+                    for (from, to, new_from) in ranges {
+                        if from == to {
+                            writeln!(f, "    Pos: {} -> {}", from + 1, new_from + 1)?;
+                        } else {
+                            writeln!(
+                                f,
+                                "    Pos: {}-{} -> {}-{}",
+                                from + 1,
+                                to + 1,
+                                new_from + 1,
+                                new_from + to - from + 1
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Operation::Import(filename, jobs, new_tags) => {
+                let count = jobs.len();
                 if new_tags.is_empty() {
                     write!(f, "Imported {count} jobs from {filename}.")
                 } else {
@@ -80,6 +190,7 @@ impl std::fmt::Display for Operation {
                     )
                 }
             }
+            Operation::Merge(merge) => write!(f, "{merge}"),
             Operation::Configure(tags, config) => {
                 if let Some(tags) = tags {
                     write!(
@@ -95,7 +206,7 @@ impl std::fmt::Display for Operation {
                     )
                 }
             }
-            Operation::List(_, range, tags) => {
+            Operation::List(_, range, tags, _) => {
                 if let Some(tags) = tags {
                     write!(f, "Listed {range} with tags {tags}.")?;
                 } else {
@@ -103,7 +214,15 @@ impl std::fmt::Display for Operation {
                 }
                 Ok(())
             }
-            Operation::Report(_, range, tags) => {
+            Operation::ListDeleted(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Listed deleted {range} with tags {tags}.")?;
+                } else {
+                    write!(f, "Listed deleted {range}:")?;
+                }
+                Ok(())
+            }
+            Operation::Report(_, range, tags, _) => {
                 if let Some(tags) = tags {
                     write!(f, "Reported {range} with tags {tags}.")?;
                 } else {
@@ -111,7 +230,15 @@ impl std::fmt::Display for Operation {
                 }
                 Ok(())
             }
-            Operation::ExportCSV(_, range, tags, columns) => {
+            Operation::Balance(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Balanced {range} with tags {tags}.")?;
+                } else {
+                    write!(f, "Balanced {range}:")?;
+                }
+                Ok(())
+            }
+            Operation::ExportCSV(_, range, tags, columns, _) => {
                 if let Some(tags) = tags {
                     write!(f, "Exported {columns} from {range} with tags {tags}.")?;
                 } else {
@@ -119,6 +246,38 @@ impl std::fmt::Display for Operation {
                 }
                 Ok(())
             }
+            Operation::ExportJSON(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Exported {range} with tags {tags} as JSON.")?;
+                } else {
+                    write!(f, "Exported {range} as JSON:")?;
+                }
+                Ok(())
+            }
+            Operation::ExportICS(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Exported {range} with tags {tags} as iCalendar.")?;
+                } else {
+                    write!(f, "Exported {range} as iCalendar:")?;
+                }
+                Ok(())
+            }
+            Operation::ExportTimewarrior(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Exported {range} with tags {tags} as Timewarrior data.")?;
+                } else {
+                    write!(f, "Exported {range} as Timewarrior data:")?;
+                }
+                Ok(())
+            }
+            Operation::ExportTimeclock(_, range, tags) => {
+                if let Some(tags) = tags {
+                    write!(f, "Exported {range} with tags {tags} as timeclock.")?;
+                } else {
+                    write!(f, "Exported {range} as timeclock:")?;
+                }
+                Ok(())
+            }
             Operation::ListTags(tags) => {
                 if tags.is_empty() {
                     write!(f, "Currently no tags are used.")
@@ -140,6 +299,23 @@ impl std::fmt::Display for Operation {
                 }
                 Ok(())
             }
+            Operation::Output(filename, operation) => {
+                write!(f, "{operation}\nOutput written into file '{filename}'.")
+            }
+            Operation::Undo(entries) => {
+                writeln!(f, "Undid {} operation(s):\n", entries.len())?;
+                for entry in entries {
+                    writeln!(f, "    {entry}")?;
+                }
+                Ok(())
+            }
+            Operation::Redo(entries) => {
+                writeln!(f, "Redid {} operation(s):\n", entries.len())?;
+                for entry in entries {
+                    writeln!(f, "    {entry}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -1,7 +1,7 @@
 //! Partial date and time like it is entered by the user.
 
 use super::prelude::*;
-use chrono::{Datelike, Local, TimeZone, Timelike, Utc};
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Timelike, Utc, Weekday};
 use regex::Regex;
 
 /// Partial date and time in different flavors.
@@ -34,9 +34,11 @@ pub enum PartialDateTime {
 
 impl PartialDateTime {
     /// Parse a partial date from optional string.
-    pub fn parse(dt: Option<String>) -> Result<Self, Error> {
+    ///
+    /// Relative expressions (like `-15m` or `yesterday`) get resolved by using the time of `context`.
+    pub fn parse(dt: Option<String>, context: &Context) -> Result<Self, Error> {
         if let Some(dt) = dt {
-            match Self::parse_date_time(dt.clone()) {
+            match Self::parse_date_time(dt.clone(), context) {
                 PartialDateTime::None => Err(Error::PartialDateTimeFormat(dt)),
                 pdt => Ok(pdt),
             }
@@ -45,9 +47,9 @@ impl PartialDateTime {
         }
     }
     /// Parse optional partial date from optional string.
-    pub fn parse_opt(dt: Option<String>) -> Self {
+    pub fn parse_opt(dt: Option<String>, context: &Context) -> Self {
         if let Some(dt) = dt {
-            Self::parse_date_time(dt)
+            Self::parse_date_time(dt, context)
         } else {
             Self::None
         }
@@ -99,29 +101,80 @@ impl PartialDateTime {
         Self::None
     }
     /// Parse date and time from `String`.
-    fn parse_date_time(dt: String) -> Self {
+    fn parse_date_time(dt: String, context: &Context) -> Self {
         let dt: Vec<&str> = dt.split(',').collect();
+        let parse_date = |dt: &str| {
+            Self::parse_dmy(dt)
+                .or(Self::parse_mdy(dt)
+                    .or(Self::parse_ymd(dt).or(Self::parse_dm(dt)
+                        .or(Self::parse_md(dt).or(Self::parse_day_name(dt, context))))))
+        };
         match dt.len() {
-            1 => Self::parse_dmy(dt[0]).or(Self::parse_mdy(dt[0]).or(Self::parse_ymd(dt[0])
-                .or(Self::parse_dm(dt[0]).or(Self::parse_md(dt[0]).or(Self::parse_hm(dt[0])))))),
-            2 => {
-                Self::merge(
-                    Self::parse_dmy(dt[0])
-                        .or(Self::parse_mdy(dt[0]).or(Self::parse_ymd(dt[0])
-                            .or(Self::parse_dm(dt[0]).or(Self::parse_md(dt[0]))))),
-                    Self::parse_hm(dt[1]),
-                )
-                .or(Self::merge(
-                    Self::parse_dmy(dt[1])
-                        .or(Self::parse_mdy(dt[1]).or(Self::parse_ymd(dt[1])
-                            .or(Self::parse_dm(dt[1]).or(Self::parse_md(dt[1]))))),
-                    Self::parse_hm(dt[0]),
-                ))
-            }
+            1 => Self::parse_offset(dt[0], context).or(parse_date(dt[0]).or(Self::parse_hm(dt[0]))),
+            2 => Self::merge(parse_date(dt[0]), Self::parse_hm(dt[1]))
+                .or(Self::merge(parse_date(dt[1]), Self::parse_hm(dt[0]))),
             _ => PartialDateTime::None,
         }
     }
 
+    /// Parse offset from the current time like `-15m`, `+1h`, `-1d2h30m` or `+PT1H30M`.
+    fn parse_offset(dt: &str, context: &Context) -> Self {
+        if dt.starts_with(['+', '-']) {
+            if let Ok(offset) = Duration::parse(dt.strip_prefix('+').unwrap_or(dt).into()) {
+                let time = (context.time() + offset).into_local();
+                return Self::YMDHM {
+                    year: time.year(),
+                    month: time.month(),
+                    day: time.day(),
+                    hour: time.hour(),
+                    minute: time.minute(),
+                };
+            }
+        }
+        Self::None
+    }
+
+    /// Parse named day like `today`, `yesterday`, `tomorrow`, `mon` or `last friday`.
+    ///
+    /// A weekday means the latest such day until today and with a leading `last` the one before today.
+    fn parse_day_name(dt: &str, context: &Context) -> Self {
+        let today = context.time().into_local().date();
+        let dt = dt.trim().to_lowercase();
+        let date = match dt.as_str() {
+            "today" => Some(today),
+            "yesterday" => today.pred_opt(),
+            "tomorrow" => today.succ_opt(),
+            _ => {
+                let (last, weekday) = match dt.strip_prefix("last ") {
+                    Some(weekday) => (true, weekday.trim()),
+                    None => (false, dt.as_str()),
+                };
+                weekday
+                    .parse::<Weekday>()
+                    .ok()
+                    .map(|weekday| Self::latest(today, weekday, last))
+            }
+        };
+        match date {
+            Some(date) => Self::YMD {
+                year: date.year(),
+                month: date.month(),
+                day: date.day(),
+            },
+            None => Self::None,
+        }
+    }
+
+    /// Find latest date with the given `weekday` until `today` (or before `today` if `exclusive`).
+    fn latest(today: NaiveDate, weekday: Weekday, exclusive: bool) -> NaiveDate {
+        let mut days =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        if exclusive && days == 0 {
+            days = 7;
+        }
+        today - chrono::Duration::days(days as i64)
+    }
+
     /// Parse time from "HH:MM" format.
     fn parse_hm(dt: &str) -> Self {
         let re = Regex::new(r"^(\d{1,2}):(\d{1,2})$").unwrap();
@@ -193,6 +246,10 @@ impl PartialDateTime {
         }
         Self::None
     }
+    /// Return `true` if only a date (without time) is given.
+    pub fn is_date(&self) -> bool {
+        matches!(self, Self::MD { .. } | Self::YMD { .. })
+    }
     /// Convert partial date and time into date and time by enriching it with data from `base`.
     pub fn into(self, base: DateTime) -> DateTime {
         let base: chrono::DateTime<Local> = base.into();
@@ -244,7 +301,45 @@ impl PartialDateTime {
 /// Test date and time parsing.
 #[test]
 fn test_parse_date_time() {
-    PartialDateTime::parse_date_time("1.1.,12:00".into());
+    let context = Context::new_test("2023-2-1 12:00");
+    assert_eq!(
+        PartialDateTime::parse_date_time("1.1.,12:00".into(), &context),
+        PartialDateTime::MDHM {
+            month: 1,
+            day: 1,
+            hour: 12,
+            minute: 0
+        }
+    );
+}
+
+/// Test relative and named date and time parsing (2023-2-1 is a wednesday).
+#[test]
+fn test_parse_relative() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let parse = |dt: &str| PartialDateTime::parse_date_time(dt.into(), &context);
+    let ymdhm = |year, month, day, hour, minute| PartialDateTime::YMDHM {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+    };
+    let ymd = |year, month, day| PartialDateTime::YMD { year, month, day };
+
+    assert_eq!(parse("-15m"), ymdhm(2023, 2, 1, 11, 45));
+    assert_eq!(parse("+1h"), ymdhm(2023, 2, 1, 13, 0));
+    assert_eq!(parse("-1d12h30m"), ymdhm(2023, 1, 30, 23, 30));
+    assert_eq!(parse("today"), ymd(2023, 2, 1));
+    assert_eq!(parse("yesterday,9:00"), ymdhm(2023, 1, 31, 9, 0));
+    assert_eq!(parse("Tomorrow"), ymd(2023, 2, 2));
+    assert_eq!(parse("mon"), ymd(2023, 1, 30));
+    assert_eq!(parse("wednesday"), ymd(2023, 2, 1));
+    assert_eq!(parse("last wed"), ymd(2023, 1, 25));
+    assert_eq!(parse("8:00,last friday"), ymdhm(2023, 1, 27, 8, 0));
+    assert_eq!(parse("-"), PartialDateTime::None);
+    assert_eq!(parse("-1x"), PartialDateTime::None);
+    assert_eq!(parse("someday"), PartialDateTime::None);
 }
 
 /// Test time parsing.
@@ -28,6 +28,12 @@ impl PositionalRanges {
     }
 }
 
+impl Default for PositionalRanges {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::fmt::Display for PositionalRanges {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -73,6 +79,14 @@ impl Positions {
     pub fn contains(&self, position: &usize) -> bool {
         self.0.contains(position)
     }
+    /// Return `true` if list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Return number of positions in list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 /// Iterator over positions.
@@ -1,6 +1,7 @@
 //! Temporal or positional range of jobs within the database.
 
 use super::prelude::*;
+use chrono::{Datelike, Month, NaiveDate, Weekday};
 use regex::Regex;
 
 /// Descriptor of temporal or positional range of jobs within the database.
@@ -14,6 +15,8 @@ pub enum Range {
     Count(usize),
     /// At given positions.
     At(Vec<usize>),
+    /// Jobs with the given IDs (or unique prefixes of them).
+    Ids(Vec<String>),
     /// From position to position
     PositionRange(usize, usize),
     /// From position to the end.
@@ -24,19 +27,18 @@ pub enum Range {
     TimeRange(DateTime, DateTime),
     /// All jobs which overlap the time since a specified time.
     Since(DateTime),
+    /// All jobs which match a filter expression.
+    Filter(Box<Filter>),
 }
 
 impl Range {
     /// Parse a range from a string like told in the manual.
     pub fn parse(list: Option<String>, context: &Context) -> Result<Self, Error> {
         if let Some(list) = list {
-            match Self::parse_count(&list).or(Self::parse_at(&list).or(Self::parse_position_range(
-                &list,
-            )
-            .or(
-                Self::parse_time_range(&list, context).or(Self::parse_day(&list, context)
-                    .or(Self::parse_from_position(&list).or(Self::parse_since(&list, context)))),
-            ))) {
+            if Filter::is_expression(&list) {
+                return Ok(Range::Filter(Box::new(Filter::parse(&list, context)?)));
+            }
+            match Self::parse_simple(&list, context) {
                 Range::None => Err(Error::RangeFormat(list)),
                 range => Ok(range),
             }
@@ -44,6 +46,83 @@ impl Range {
             Ok(Range::All)
         }
     }
+    /// Parse a range which is not a filter expression (returns `Range::None` if parsing fails).
+    pub fn parse_simple(list: &str, context: &Context) -> Self {
+        Self::parse_period(list, context).or(Self::parse_count(list).or(Self::parse_at(list)
+            .or(Self::parse_ids(list))
+            .or(
+                Self::parse_position_range(list).or(Self::parse_time_range(list, context)
+                    .or(Self::parse_day(list, context)
+                        .or(Self::parse_from_position(list).or(Self::parse_since(list, context))))),
+            )))
+    }
+    /// Return the message searches of a filter expression (to highlight their matches).
+    pub fn searches(&self) -> Vec<Search> {
+        if let Range::Filter(filter) = self {
+            filter.searches()
+        } else {
+            Vec::new()
+        }
+    }
+    /// Return `true` if the job at position `pos` is within this range.
+    ///
+    /// Counts always match (jobs have to be drained afterwards) and IDs never (they have to be resolved into positions before).
+    pub fn contains(&self, pos: usize, job: &Job) -> bool {
+        match self {
+            Range::None | Range::Ids(_) => false,
+            Range::All | Range::Count(_) => true,
+            Range::At(positions) => positions.contains(&pos),
+            Range::PositionRange(f, t) => pos >= *f && pos <= *t,
+            Range::FromPosition(p) => pos >= *p,
+            Range::Day(d) => {
+                job.start.date() <= *d
+                    && if let Some(end) = job.end {
+                        end.date() >= *d
+                    } else {
+                        true
+                    }
+            }
+            Range::TimeRange(f, t) => {
+                job.start < *t
+                    && if let Some(end) = job.end {
+                        end >= *f
+                    } else {
+                        true
+                    }
+            }
+            Range::Since(s) => {
+                job.start >= *s
+                    || if let Some(end) = job.end {
+                        end >= *s
+                    } else {
+                        true
+                    }
+            }
+            Range::Filter(filter) => filter.matches(pos, job),
+        }
+    }
+    /// Return the start of a time range or `None` if this range has no start in time.
+    pub fn start(&self) -> Option<DateTime> {
+        match self {
+            Range::TimeRange(from, _) if *from != Self::open_start() => Some(*from),
+            Range::Since(from) => Some(*from),
+            Range::Day(day) => Some(day.midnight()),
+            _ => None,
+        }
+    }
+    /// Return the (exclusive) end of a time range or `None` if this range has no end in time.
+    pub fn end(&self) -> Option<DateTime> {
+        match self {
+            Range::TimeRange(_, to) => Some(*to),
+            Range::Day(day) => Some(day.next_midnight()),
+            _ => None,
+        }
+    }
+    /// Start of time ranges which are open at the beginning (like `..3/31`).
+    fn open_start() -> DateTime {
+        use chrono::{TimeZone, Utc};
+        Utc.with_ymd_and_hms(1900, 1, 1, 0, 0, 0).unwrap().into()
+    }
     /// Return self or another.
     fn or(self, other: Self) -> Self {
         match self {
@@ -51,6 +130,101 @@ impl Range {
             _ => self,
         }
     }
+    /// Parse calendar period (week, month, quarter or year).
+    fn parse_period(list: &str, context: &Context) -> Range {
+        let today = context.time().into_local().date();
+        let (year, month) = (today.year(), today.month() as i32);
+        let quarter = (month - 1) / 3 * 3 + 1;
+        let list = list.to_lowercase();
+        match list.as_str() {
+            "this-week" => Self::week(today),
+            "last-week" => Self::week(today - chrono::Duration::days(7)),
+            "this-month" => Self::months(year, month, 1),
+            "last-month" => Self::months(year, month - 1, 1),
+            "this-quarter" => Self::months(year, quarter, 3),
+            "last-quarter" => Self::months(year, quarter - 3, 3),
+            "this-year" => Self::months(year, 1, 12),
+            "last-year" => Self::months(year - 1, 1, 12),
+            "ytd" => match Self::months(year, 1, 12) {
+                Self::TimeRange(from, _) => Self::TimeRange(from, context.time()),
+                range => range,
+            },
+            _ => Self::parse_week(&list, today).or(Self::parse_month(&list, today)
+                .or(Self::parse_quarter(&list, today).or(Self::parse_year(&list)))),
+        }
+    }
+    /// Parse ISO week like `w12` (of current year) or `2023-W12`.
+    fn parse_week(list: &str, today: NaiveDate) -> Range {
+        let re = Regex::new(r"^(?:(\d{4})-)?w(\d{1,2})$").unwrap();
+        if let Some(cap) = re.captures(list) {
+            let year = cap
+                .get(1)
+                .map(|year| year.as_str().parse::<i32>().unwrap())
+                .unwrap_or(today.iso_week().year());
+            if let Some(monday) =
+                NaiveDate::from_isoywd_opt(year, cap[2].parse::<u32>().unwrap(), Weekday::Mon)
+            {
+                return Self::week(monday);
+            }
+        }
+        Self::None
+    }
+    /// Parse month like `2023-03` or a month name like `march` or `mar` (of current year).
+    fn parse_month(list: &str, today: NaiveDate) -> Range {
+        let re = Regex::new(r"^(\d{4})-(\d{2})$").unwrap();
+        if let Some(cap) = re.captures(list) {
+            let month = cap[2].parse::<i32>().unwrap();
+            if (1..=12).contains(&month) {
+                return Self::months(cap[1].parse::<i32>().unwrap(), month, 1);
+            }
+        } else if let Ok(month) = list.parse::<Month>() {
+            return Self::months(today.year(), month.number_from_month() as i32, 1);
+        }
+        Self::None
+    }
+    /// Parse quarter like `Q1` (of current year) or `2023-Q1`.
+    fn parse_quarter(list: &str, today: NaiveDate) -> Range {
+        let re = Regex::new(r"^(?:(\d{4})-)?q([1-4])$").unwrap();
+        if let Some(cap) = re.captures(list) {
+            let year = cap
+                .get(1)
+                .map(|year| year.as_str().parse::<i32>().unwrap())
+                .unwrap_or(today.year());
+            return Self::months(year, cap[2].parse::<i32>().unwrap() * 3 - 2, 3);
+        }
+        Self::None
+    }
+    /// Parse year like `y2023` (prefixed to be distinguishable from positions).
+    fn parse_year(list: &str) -> Range {
+        let re = Regex::new(r"^y(\d{4})$").unwrap();
+        if let Some(cap) = re.captures(list) {
+            return Self::months(cap[1].parse::<i32>().unwrap(), 1, 12);
+        }
+        Self::None
+    }
+    /// Time range of the week (starting monday) which includes `date`.
+    fn week(date: NaiveDate) -> Range {
+        let monday = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        Self::TimeRange(
+            Self::midnight(monday),
+            Self::midnight(monday + chrono::Duration::days(7)),
+        )
+    }
+    /// Time range of `count` months starting with the given month (which may be below 1 to count into the previous year).
+    fn months(year: i32, month: i32, count: i32) -> Range {
+        let first = |month: i32| {
+            let month = year * 12 + month - 1;
+            Self::midnight(
+                NaiveDate::from_ymd_opt(month.div_euclid(12), month.rem_euclid(12) as u32 + 1, 1)
+                    .unwrap(),
+            )
+        };
+        Self::TimeRange(first(month), first(month + count))
+    }
+    /// Local midnight at the beginning of `date`.
+    fn midnight(date: NaiveDate) -> DateTime {
+        DateTime::from_local(&date.and_hms_opt(0, 0, 0).unwrap())
+    }
     /// Parse `Count`.
     fn parse_count(list: &str) -> Range {
         let re = Regex::new(r"^~(\d+)$").unwrap();
@@ -73,6 +247,14 @@ impl Range {
         }
         Self::None
     }
+    /// Parse `Ids`.
+    fn parse_ids(list: &str) -> Range {
+        let re = Regex::new(r"^@[[:xdigit:]]+(,@[[:xdigit:]]+)*$").unwrap();
+        if re.is_match(list) {
+            return Self::Ids(list.split(',').map(|id| id[1..].to_lowercase()).collect());
+        }
+        Self::None
+    }
     /// Parse `PositionRange`.
     fn parse_position_range(list: &str) -> Range {
         let re = Regex::new(r"^(\d+)-(\d+)$").unwrap();
@@ -94,7 +276,7 @@ impl Range {
     }
     /// Parse `Day`.
     fn parse_day(list: &str, context: &Context) -> Range {
-        let pt = PartialDateTime::parse_opt(Some(list.to_string()));
+        let pt = PartialDateTime::parse_opt(Some(list.to_string()), context);
         match pt {
             PartialDateTime::None => Self::None,
             _ => Range::Day(Date::from(pt.into(context.time()))),
@@ -114,34 +296,38 @@ impl Range {
             }
         };
         if list.len() == 2 {
-            let from = PartialDateTime::parse_opt(Some(list[0].to_string()));
-            let to = PartialDateTime::parse_opt(Some(list[1].to_string()));
+            let from = PartialDateTime::parse_opt(Some(list[0].to_string()), context);
+            let to = PartialDateTime::parse_opt(Some(list[1].to_string()), context);
             match (from, to) {
                 (PartialDateTime::None, PartialDateTime::None) => Self::None,
                 (from, PartialDateTime::None) => {
                     Self::TimeRange(from.into(context.time()), context.time())
                 }
                 (PartialDateTime::None, to) => {
-                    use chrono::{TimeZone, Utc};
-                    Self::TimeRange(
-                        Utc.with_ymd_and_hms(1900, 1, 1, 0, 0, 0).unwrap().into(),
-                        to.into(context.time()) + Duration::days(1),
-                    )
+                    Self::TimeRange(Self::open_start(), Self::end_of(to, context.time()))
                 }
                 (from, to) => {
                     let from = from.into(context.time());
-                    Self::TimeRange(from, to.into(from) + Duration::days(1))
+                    Self::TimeRange(from, Self::end_of(to, from))
                 }
             }
         } else {
             Self::None
         }
     }
+    /// Resolve end of a time range which includes the whole day if only a date is given.
+    fn end_of(to: PartialDateTime, base: DateTime) -> DateTime {
+        if to.is_date() {
+            to.into(base) + Duration::days(1)
+        } else {
+            to.into(base)
+        }
+    }
     /// Parse `Since`.
     fn parse_since(list: &str, context: &Context) -> Range {
         let re = Regex::new(r"^(.+)\.\.$").unwrap();
         if let Some(cap) = re.captures_iter(list).next() {
-            let pt = PartialDateTime::parse_opt(Some(cap[1].to_string()));
+            let pt = PartialDateTime::parse_opt(Some(cap[1].to_string()), context);
             return match pt {
                 PartialDateTime::None => Self::None,
                 _ => Range::Since(pt.into(context.time())),
@@ -165,6 +351,14 @@ impl std::fmt::Display for Range {
                     v.join(",")
                 }
             ),
+            Self::Ids(ids) => write!(
+                f,
+                "job(s) with ID(s) {ids}",
+                ids = {
+                    let v: Vec<String> = ids.iter().map(|id| format!("@{id}")).collect();
+                    v.join(",")
+                }
+            ),
             Self::PositionRange(from, to) => write!(
                 f,
                 "job(s) from position {from} to {to}",
@@ -175,6 +369,7 @@ impl std::fmt::Display for Range {
             Self::Day(day) => write!(f, "job(s) at {day}"),
             Self::TimeRange(since, until) => write!(f, "job(s) since {since} until {until}"),
             Self::Since(since) => write!(f, "job(s) since {since}"),
+            Self::Filter(filter) => write!(f, "job(s) matching '{filter}'"),
         }
     }
 }
@@ -230,3 +425,33 @@ fn test_parse_fails() {
 
     assert!(Range::parse(Some("1.1.-".into()), &context).is_err());
 }
+
+/// Test parsing of calendar periods (2023-2-1 is a wednesday).
+#[test]
+fn test_parse_period() {
+    let context = Context::new_test("2023-2-1 12:00");
+    let parse = |list: &str| Range::parse(Some(list.into()), &context).unwrap();
+    let range = |from: &str, to: &str| Range::TimeRange(from.into(), to.into());
+
+    assert_eq!(parse("w5"), range("2023-1-30 0:00", "2023-2-6 0:00"));
+    assert_eq!(parse("2023-W12"), range("2023-3-20 0:00", "2023-3-27 0:00"));
+    assert_eq!(parse("2023-03"), range("2023-3-1 0:00", "2023-4-1 0:00"));
+    assert_eq!(parse("March"), range("2023-3-1 0:00", "2023-4-1 0:00"));
+    assert_eq!(parse("dec"), range("2023-12-1 0:00", "2024-1-1 0:00"));
+    assert_eq!(parse("Q1"), range("2023-1-1 0:00", "2023-4-1 0:00"));
+    assert_eq!(parse("2022-q4"), range("2022-10-1 0:00", "2023-1-1 0:00"));
+    assert_eq!(parse("Y2022"), range("2022-1-1 0:00", "2023-1-1 0:00"));
+    assert_eq!(parse("this-week"), range("2023-1-30 0:00", "2023-2-6 0:00"));
+    assert_eq!(parse("last-month"), range("2023-1-1 0:00", "2023-2-1 0:00"));
+    assert_eq!(
+        parse("last-quarter"),
+        range("2022-10-1 0:00", "2023-1-1 0:00")
+    );
+    assert_eq!(parse("ytd"), range("2023-1-1 0:00", "2023-2-1 12:00"));
+
+    // still positions
+    assert_eq!(parse("12"), Range::At(vec![11]));
+    assert_eq!(parse("1-12"), Range::PositionRange(0, 11));
+    assert_eq!(parse("2023"), Range::At(vec![2022]));
+    assert!(Range::parse(Some("w54".into()), &context).is_err());
+}
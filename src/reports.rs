@@ -10,199 +10,235 @@ use std::collections::HashMap;
 #[cfg(feature = "colors")]
 use termion::{color::*, style};
 
-/// Report in calendar form.
-pub fn report<W: std::io::Write>(mut w: W, jobs: &JobList, context: &Context) -> Result<(), Error> {
+/// Hours and pay of a single day within a [CalendarMonth].
+#[derive(Debug, Clone)]
+pub struct CalendarDay {
+    /// Hours worked at this day.
+    pub hours: f64,
+    /// Payment for this day if any rate is configured.
+    pub pay: Option<f64>,
+    /// `true` if maximum hours of any tag were exceeded.
+    pub exceeded: bool,
+}
+
+/// Week within a [CalendarMonth] which starts with sunday.
+#[derive(Debug, Clone)]
+pub struct CalendarWeek {
+    /// Day of month of the sunday which starts this week (`None` in first week of month).
+    pub label: Option<u32>,
+    /// Seven days of this week which are `None` if not within the month or `Some(None)` if nothing was done.
+    pub days: Vec<Option<Option<CalendarDay>>>,
+    /// Hours worked in this week.
+    pub hours: f64,
+}
+
+/// Month within a calendar (which has no work done only within a balance).
+#[derive(Debug, Clone)]
+pub struct CalendarMonth {
+    pub year: i32,
+    pub month: u32,
+    pub weeks: Vec<CalendarWeek>,
+    /// Hours worked in this month.
+    pub hours: f64,
+    /// Payment for this month if any rate is configured.
+    pub pay: Option<f64>,
+}
+
+/// Names of the week days in calendar order.
+pub const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Short names of the months.
+pub const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Hours per tag and day of month.
+type Days = HashMap<u32, HashMap<Option<String>, f64>>;
+
+/// Sort hours of jobs into calendar months.
+pub fn calendar(jobs: &JobList, context: &Context) -> Vec<CalendarMonth> {
     // resort job hours into nested maps of year -> month -> day -> hours
-    type Days = HashMap<u32, HashMap<Option<String>, f64>>;
     type Month = HashMap<u32, Days>;
     let mut years: HashMap<i32, Month> = HashMap::new();
     for (_, job) in jobs.iter() {
         for job in job.split(context) {
-            // insert year if not already in map
-            let year = job.start.year();
-            years.entry(year).or_insert_with(HashMap::new);
-            // get months in that year
-            let months = years.get_mut(&year).unwrap();
-
-            // insert month if not already in year
-            let month = job.start.month();
-            months.entry(month).or_insert_with(HashMap::new);
-            // get days in that month
-            let days = months.get_mut(&month).unwrap();
-
-            // insert day if not already in month
-            let day = job.start.day();
-            days.entry(day).or_insert_with(HashMap::new);
-            // get tagged hours of that day
-            let tag_hours = days.get_mut(&day).unwrap();
-
             // get configuration for the job's tags and the tag which was relevant
             let (tag, properties) = jobs.configuration.get_and_why(&job.tags);
 
-            // get hours for that tag
-            let job_hours = job.hours(properties);
-            if !tag_hours.contains_key(&tag) {
-                tag_hours.insert(tag.clone(), 0.0);
-            }
-            // get hours of that day and that tag
-            let hours = tag_hours.get_mut(&tag).unwrap();
-
             // add job hours to that day and that tag
-            *hours += job_hours;
+            *years
+                .entry(job.start.year())
+                .or_default()
+                .entry(job.start.month())
+                .or_default()
+                .entry(job.start.day())
+                .or_default()
+                .entry(tag)
+                .or_default() += job.hours(properties);
         }
     }
 
-    // enumerate all years in map in sorted order
+    let mut calendar = Vec::new();
+    // enumerate all months of all years in sorted order
     for (year, months) in years.iter().sorted_by_key(|x| x.0) {
-        let mut month_hours = 0.0;
-        let mut month_costs: Option<f64> = None;
-        // enumerate all months in that year in sorted order
         for (month, days) in months.iter().sorted_by_key(|x| x.0) {
-            // print year/month title centered
-            let month_year = format!("{}/{}", month, year);
-            writeln!(w, "{:^68}", month_year)?;
-
-            // insert day of month column
-            write!(w, "{:>3}", "Day")?;
+            calendar.push(calendar_month(*year, *month, days, jobs));
+        }
+    }
+    calendar
+}
 
-            // print weekdays as table header
-            const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-            for weekday in WEEKDAYS {
-                write!(w, "{:>8}", weekday)?;
+/// Sort hours of a single month into weeks.
+fn calendar_month(year: i32, month: u32, days: &Days, jobs: &JobList) -> CalendarMonth {
+    // indent to first weekday in this month
+    let first_weekday = NaiveDate::from_ymd_opt(year, month, 1)
+        .unwrap()
+        .weekday()
+        .num_days_from_sunday() as usize;
+    let mut weeks = vec![CalendarWeek {
+        label: None,
+        days: vec![None; first_weekday],
+        hours: 0.0,
+    }];
+    let mut month_hours = 0.0;
+    let mut month_pay: Option<f64> = None;
+    for day in 1..=days_in_month(year, month) {
+        // start a new week at sunday
+        if day > 1 && NaiveDate::from_ymd_opt(year, month, day).unwrap().weekday() == Weekday::Sun {
+            weeks.push(CalendarWeek {
+                label: Some(day),
+                days: Vec::new(),
+                hours: 0.0,
+            });
+        }
+        let week = weeks.last_mut().unwrap();
+        // sum up all hours at this day and determine if work limit is exceeded for any tag
+        let day = days.get(&day).map(|tag_hours| {
+            let mut day = CalendarDay {
+                hours: 0.0,
+                pay: None,
+                exceeded: false,
+            };
+            for (tag, hours) in tag_hours {
+                let properties = jobs
+                    .configuration
+                    .get_checked(&tag.into())
+                    .expect("unexpected tag collision");
+                if let Some(max_hours) = properties.max_hours {
+                    if *hours > max_hours as f64 {
+                        day.exceeded = true;
+                    }
+                }
+                day.hours += hours;
+                if let Some(rate) = properties.rate {
+                    day.pay = Some(day.pay.unwrap_or_default() + hours * rate);
+                }
             }
-            // add weekly sum to table header
-            writeln!(w, "{:>8}", "Week")?;
-
-            // indent day of month column
-            write!(w, "{:>3}", "")?;
-
-            // indent to first weekday in this month
-            let first_weekday = NaiveDate::from_ymd_opt(*year, *month, 1)
-                .unwrap()
-                .weekday()
-                .num_days_from_sunday()
-                + 1;
-            for _ in 1..first_weekday {
-                write!(w, "{:>8}", " ")?;
+            // sum up weekly and monthly hours
+            week.hours += day.hours;
+            month_hours += day.hours;
+            if let Some(pay) = day.pay {
+                month_pay = Some(month_pay.unwrap_or_default() + pay);
             }
+            day
+        });
+        week.days.push(Some(day));
+    }
+    weeks.last_mut().unwrap().days.resize(7, None);
+    CalendarMonth {
+        year,
+        month,
+        weeks,
+        hours: month_hours,
+        pay: month_pay,
+    }
+}
 
-            // print all days in this month week per week
-            let mut week_hours = 0.0;
-            let mut week_day_number = 0;
-            for day in 1..days_in_month(*year, *month) {
-                // if we reach sunday
-                if NaiveDate::from_ymd_opt(*year, *month, day)
-                    .unwrap()
-                    .weekday()
-                    == Weekday::Sun
-                {
-                    // print weekly sum and restart a new week row
-                    writeln!(w, "{:>8}", week_hours)?;
-
-                    // re-initialize weekly hours sum
-                    week_hours = 0.0;
-                    week_day_number = 0;
-
-                    // indent day of month column
-                    write!(w, "{:>3}", day)?;
-                }
+/// Report in calendar form.
+pub fn report<W: std::io::Write>(mut w: W, jobs: &JobList, context: &Context) -> Result<(), Error> {
+    for month in calendar(jobs, context) {
+        // print year/month title centered
+        let month_year = format!("{}/{}", month.month, month.year);
+        writeln!(w, "{:^68}", month_year)?;
+
+        // insert day of month column
+        write!(w, "{:>3}", "Day")?;
 
-                // print hours of that day if any or '-'
-                if let Some(tag_hours) = days.get(&day) {
-                    // sum up all hours at this day and determine if work limit is exceeded for any tag
-                    let mut day_hours = 0.0;
-                    let mut day_costs: Option<f64> = None;
-                    
-                    #[cfg(feature = "colors")]
-                    let mut exceeded = false;
-                    for (tag, hours) in tag_hours {
-                        let properties = jobs
-                            .configuration
-                            .get_checked(&tag.into())
-                            .expect("unexpected tag collision");
-                        
+        // print weekdays as table header
+        for weekday in WEEKDAYS {
+            write!(w, "{:>8}", weekday)?;
+        }
+        // add weekly sum to table header
+        writeln!(w, "{:>8}", "Week")?;
+
+        // print all days in this month week per week
+        for week in &month.weeks {
+            // print day of month column
+            if let Some(label) = week.label {
+                write!(w, "{:>3}", label)?;
+            } else {
+                write!(w, "{:>3}", "")?;
+            }
+            for day in &week.days {
+                match day {
+                    // print hours at this day and mark yellow if exceeded and red if >24h/day
+                    Some(Some(day)) => {
                         #[cfg(feature = "colors")]
-                        if let Some(max_hours) = properties.max_hours {
-                            if *hours > max_hours as f64 {
-                                exceeded = true;
+                        {
+                            write!(w, "{}", style::Bold)?;
+                            if day.hours > 24.0 {
+                                write!(w, "{}{:>8}{}", Fg(LightRed), day.hours, Fg(Reset),)?;
+                            } else if day.exceeded {
+                                write!(w, "{}{:>8}{}", Fg(Yellow), day.hours, Fg(Reset),)?;
+                            } else {
+                                write!(w, "{}{:>8}{}", Fg(LightWhite), day.hours, Fg(Reset),)?;
                             }
+                            write!(w, "{}", style::Reset)?;
                         }
-                        day_hours += hours;
-                        if let Some(rate) = properties.rate {
-                            if day_costs.is_none() {
-                                day_costs = Some(0.0);
-                            }
-                            day_costs = Some(day_costs.unwrap() + hours * rate);
-                        }
-                    }
 
-                    #[cfg(feature = "colors")] {
-                        // print hours at this day and mark yellow if exceeded and red if >24h/day
-                        write!(w, "{}", style::Bold)?;
-                        if day_hours > 24.0 {
-                            write!(w, "{}{:>8}{}", Fg(LightRed), day_hours, Fg(Reset),)?;
-                        } else if exceeded {
-                            write!(w, "{}{:>8}{}", Fg(Yellow), day_hours, Fg(Reset),)?;
-                        } else {
-                            write!(w, "{}{:>8}{}", Fg(LightWhite), day_hours, Fg(Reset),)?;
+                        #[cfg(not(feature = "colors"))]
+                        {
+                            write!(w, "{:>8}", day.hours)?;
                         }
-                        write!(w, "{}", style::Reset)?;
                     }
-
-                    #[cfg(not(feature = "colors"))] {
-                        write!(w, "{}", day_hours)?;
-                    }
-
-                    // sum up weekly and monthly hours
-                    week_hours += day_hours;
-                    month_hours += day_hours;
-                    if let Some(day_costs) = day_costs {
-                        if month_costs.is_none() {
-                            month_costs = Some(0.0);
-                        }
-                        month_costs = Some(month_costs.unwrap() + day_costs);
-                    }
-                } else {
-                    write!(w, "{:>8}", "-")?;
+                    Some(None) => write!(w, "{:>8}", "-")?,
+                    None => write!(w, "{:>8}", "")?,
                 }
-                week_day_number += 1;
-            }
-            for _ in 0..(7 - week_day_number) {
-                write!(w, "{:>8}", "")?;
             }
+            // print weekly sum
+            writeln!(w, "{:>8}", week.hours)?;
+        }
 
-            // print weekly sum and restart a new week row
-            writeln!(w, "{:>8}", week_hours)?;
-
-            const MONTHS: [&str; 12] = [
-                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
-            ];
-
-            let month_pay = {
-                if let Some(costs) = month_costs {
-                    format!(" = ${}", costs.separated_string(),)
-                } else {
-                    String::new()
-                }
-            };
+        let month_pay = {
+            if let Some(pay) = month.pay {
+                format!(
+                    " = {}{}",
+                    jobs.configuration.base.currency.unwrap_or(DEFAULT_CURRENCY),
+                    pay.separated_string()
+                )
+            } else {
+                String::new()
+            }
+        };
 
-            let monthly_hours = format!(
-                "{} {}: {} hours{}",
-                MONTHS[*month as usize - 1],
-                year,
-                month_hours,
-                month_pay
-            );
-            writeln!(w, "{:>67}", monthly_hours)?;
-            month_hours = 0.0;
-            month_costs = None;
-            writeln!(w)?;
-        }
+        let monthly_hours = format!(
+            "{} {}: {} hours{}",
+            MONTHS[month.month as usize - 1],
+            month.year,
+            month.hours,
+            month_pay
+        );
+        writeln!(w, "{:>67}", monthly_hours)?;
+        writeln!(w)?;
     }
 
     let pay = {
         if let Some(pay) = jobs.pay_overall() {
-            format!(" = ${}", format::pay_pure(pay),)
+            format!(
+                " = {}{}",
+                jobs.configuration.base.currency.unwrap_or(DEFAULT_CURRENCY),
+                format::pay_pure(pay)
+            )
         } else {
             String::new()
         }
@@ -217,3 +253,129 @@ pub fn report<W: std::io::Write>(mut w: W, jobs: &JobList, context: &Context) ->
 
     Ok(())
 }
+
+/// Round hours to two decimals to hide rounding errors of sums.
+fn round(hours: f64) -> f64 {
+    (hours * 100.0).round() / 100.0
+}
+
+/// Report hours worked compared to the configured target hours with a running flextime balance.
+///
+/// Target hours are counted from the start of the `range` (or the day of the first job if the range has no start)
+/// until today or until the end of the `range` if it is earlier.
+/// The target hours configured for the filtered `tags` are preferred over the base configuration.
+pub fn balance<W: std::io::Write>(
+    mut w: W,
+    jobs: &JobList,
+    range: &Range,
+    tags: &Option<TagSet>,
+    context: &Context,
+) -> Result<(), Error> {
+    let target = tags
+        .as_ref()
+        .map(|tags| jobs.configuration.get_checked(tags))
+        .transpose()?
+        .and_then(|properties| properties.target)
+        .or(jobs.configuration.base.target)
+        .ok_or(Error::MissingTarget)?;
+    let first = range
+        .start()
+        .map(|start| start.into_local().date())
+        .or_else(|| {
+            jobs.iter()
+                .map(|(_, job)| job.start.into_local().date())
+                .min()
+        });
+    let mut today = context.time().into_local().date();
+    if let Some(end) = range.end() {
+        // end of a range is exclusive
+        today = today.min((end - chrono::Duration::seconds(1)).into_local().date());
+    }
+
+    // fill in months without any work up to today
+    let mut months = calendar(jobs, context);
+    if let Some(first) = first {
+        let mut month = first.with_day(1).unwrap();
+        let mut pos = 0;
+        while month <= today {
+            let key = (month.year(), month.month());
+            match months.get(pos).map(|m| (m.year, m.month)) {
+                Some(existing) if existing < key => {
+                    pos += 1;
+                    continue;
+                }
+                Some(existing) if existing == key => (),
+                _ => months.insert(pos, calendar_month(key.0, key.1, &Days::new(), jobs)),
+            }
+            month = month + chrono::Months::new(1);
+            pos += 1;
+        }
+    }
+
+    let mut balance = 0.0;
+    let mut total_hours = 0.0;
+    let mut total_target = 0.0;
+    for month in months {
+        let first_of_month = NaiveDate::from_ymd_opt(month.year, month.month, 1).unwrap();
+        let first_weekday = first_of_month.weekday().num_days_from_sunday() as usize;
+
+        // print year/month title centered and table header
+        writeln!(w, "{:^43}", format!("{}/{}", month.month, month.year))?;
+        writeln!(
+            w,
+            "{:>3}{:>10}{:>10}{:>10}{:>10}",
+            "Day", "Actual", "Target", "Diff", "Balance"
+        )?;
+
+        let mut month_target = 0.0;
+        for (n, week) in month.weeks.iter().enumerate() {
+            // sum up target hours of all days in this week which are within the counted period
+            let mut week_target = 0.0;
+            for (i, _) in week
+                .days
+                .iter()
+                .enumerate()
+                .filter(|(_, day)| day.is_some())
+            {
+                let date = first_of_month + chrono::Days::new((n * 7 + i - first_weekday) as u64);
+                if first.is_some_and(|first| date >= first) && date <= today {
+                    week_target += target.hours(date.weekday());
+                }
+            }
+            month_target += week_target;
+            balance += week.hours - week_target;
+            writeln!(
+                w,
+                "{:>3}{:>10}{:>10}{:>10}{:>10}",
+                week.label.unwrap_or(1),
+                round(week.hours),
+                round(week_target),
+                format!("{:+}", round(week.hours - week_target)),
+                format!("{:+}", round(balance)),
+            )?;
+        }
+        total_hours += month.hours;
+        total_target += month_target;
+
+        let monthly_hours = format!(
+            "{} {}: {} of {} hours ({:+})",
+            MONTHS[month.month as usize - 1],
+            month.year,
+            round(month.hours),
+            round(month_target),
+            round(month.hours - month_target)
+        );
+        writeln!(w, "{:>43}", monthly_hours)?;
+        writeln!(w)?;
+    }
+
+    writeln!(
+        w,
+        "Total: {} of {} hours, flextime balance {:+} hours",
+        round(total_hours),
+        round(total_target),
+        round(balance)
+    )?;
+
+    Ok(())
+}
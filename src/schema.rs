@@ -0,0 +1,267 @@
+//! Schema versioning and migration of *jobber* database files.
+//!
+//! Every database file carries the schema version it was written with.
+//! When loading older databases they get upgraded step by step before they are deserialized.
+
+use super::prelude::*;
+use serde_json::{Map, Value};
+
+/// Schema version of databases written by this version of *jobber*.
+pub const SCHEMA: u32 = 5;
+
+/// Migrations by schema version they upgrade from (index `0` upgrades from `0` to `1` and so on).
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA as usize] = [
+    migrate_0_to_1,
+    migrate_1_to_2,
+    migrate_2_to_3,
+    migrate_3_to_4,
+    migrate_4_to_5,
+];
+
+/// Upgrade a database in JSON representation to the current [SCHEMA].
+///
+/// Databases without schema version are treated as version `0`.
+/// Returns the schema version the database had before migration or
+/// [Error::SchemaTooNew] if the database was written by a newer version of *jobber*.
+pub fn migrate(database: &mut Value) -> Result<u32, Error> {
+    let database = database
+        .as_object_mut()
+        .ok_or(Error::SchemaFormat("database is not a JSON object".into()))?;
+    let schema = match database.get("schema") {
+        None => 0,
+        Some(schema) => schema
+            .as_u64()
+            .ok_or_else(|| Error::SchemaFormat(format!("invalid schema version {schema}")))?
+            as u32,
+    };
+    if schema > SCHEMA {
+        return Err(Error::SchemaTooNew(
+            schema,
+            database
+                .get("version")
+                .and_then(|version| version.as_str())
+                .unwrap_or("?")
+                .to_string(),
+        ));
+    }
+    for migration in &MIGRATIONS[schema as usize..] {
+        migration(database);
+    }
+    database.insert("schema".into(), SCHEMA.into());
+    Ok(schema)
+}
+
+/// Upgrade from schema `0` (*jobber* up to 0.10.x) to `1`:
+///
+/// - add missing deletion mark `deleted` to jobs
+/// - rename property `pay` into `rate` in base and tag configurations
+/// - add empty undo `journal`
+fn migrate_0_to_1(database: &mut Map<String, Value>) {
+    if let Some(Value::Array(jobs)) = database.get_mut("jobs") {
+        for job in jobs.iter_mut().filter_map(|job| job.as_object_mut()) {
+            job.entry("deleted").or_insert(Value::Null);
+        }
+    }
+    fn rename_pay(properties: &mut Value) {
+        if let Some(properties) = properties.as_object_mut() {
+            if let Some(pay) = properties.remove("pay") {
+                properties.entry("rate").or_insert(pay);
+            }
+        }
+    }
+    if let Some(Value::Object(configuration)) = database.get_mut("configuration") {
+        if let Some(base) = configuration.get_mut("base") {
+            rename_pay(base);
+        }
+        if let Some(Value::Object(tags)) = configuration.get_mut("tags") {
+            tags.values_mut().for_each(rename_pay);
+        }
+    }
+    database
+        .entry("journal")
+        .or_insert(serde_json::json!({ "entries": [], "undone": 0 }));
+}
+
+/// Upgrade from schema `1` to `2`:
+///
+/// - add unique `id` to jobs
+/// - add the `id` of the related job to all jobs within the journal
+fn migrate_1_to_2(database: &mut Map<String, Value>) {
+    let mut ids = Vec::new();
+    if let Some(Value::Array(jobs)) = database.get_mut("jobs") {
+        for job in jobs.iter_mut().filter_map(|job| job.as_object_mut()) {
+            let id = job
+                .entry("id")
+                .or_insert_with(|| JobId::new().as_str().into());
+            ids.push(id.clone());
+        }
+    }
+    if let Some(journal) = database.get_mut("journal") {
+        add_journal_ids(journal, &ids);
+    }
+}
+
+/// Upgrade from schema `2` to `3`:
+///
+/// - add missing invoice number `invoice` to jobs and to all jobs within the journal
+fn migrate_2_to_3(database: &mut Map<String, Value>) {
+    add_field(database, "invoice", Value::Null);
+}
+
+/// Upgrade from schema `3` to `4`:
+///
+/// - add missing lock `locked` to jobs and to all jobs within the journal
+fn migrate_3_to_4(database: &mut Map<String, Value>) {
+    add_field(database, "locked", Value::Bool(false));
+}
+
+/// Upgrade from schema `4` to `5`:
+///
+/// - nothing to change (only SQLite databases store their journal differently)
+fn migrate_4_to_5(_database: &mut Map<String, Value>) {}
+
+/// Add a missing `field` with the given `value` to all jobs and to all jobs within the journal.
+fn add_field(database: &mut Map<String, Value>, field: &str, value: Value) {
+    if let Some(Value::Array(jobs)) = database.get_mut("jobs") {
+        for job in jobs.iter_mut().filter_map(|job| job.as_object_mut()) {
+            job.entry(field).or_insert(value.clone());
+        }
+    }
+    if let Some(journal) = database.get_mut("journal") {
+        add_journal_field(journal, field, value);
+    }
+}
+
+/// Call `f` with the position and the JSON representation of every job within a journal.
+fn for_journal_jobs(journal: &mut Value, mut f: impl FnMut(usize, &mut Map<String, Value>)) {
+    if let Some(Value::Array(entries)) = journal.get_mut("entries") {
+        for entry in entries.iter_mut() {
+            if let Some(Value::Array(changes)) = entry.get_mut("changes") {
+                for change in changes.iter_mut() {
+                    if let Some(Value::Object(change)) = change.get_mut("Job") {
+                        let pos = change
+                            .get("pos")
+                            .and_then(|pos| pos.as_u64())
+                            .unwrap_or_default() as usize;
+                        for state in ["before", "after"] {
+                            if let Some(Value::Object(job)) = change.get_mut(state) {
+                                f(pos, job);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Add the `id` of the job at the same position to all jobs within a journal in JSON representation.
+///
+/// Jobs which do not exist anymore (because their creation was undone) get a new one.
+pub fn add_journal_ids(journal: &mut Value, ids: &[Value]) {
+    let mut ids = ids.to_vec();
+    for_journal_jobs(journal, |pos, job| {
+        if pos >= ids.len() {
+            ids.resize_with(pos + 1, || JobId::new().as_str().into());
+        }
+        job.entry("id").or_insert(ids[pos].clone());
+    });
+}
+
+/// Add a missing `field` with the given `value` to all jobs within a journal in JSON representation.
+pub fn add_journal_field(journal: &mut Value, field: &str, value: Value) {
+    for_journal_jobs(journal, |_, job| {
+        job.entry(field).or_insert(value.clone());
+    });
+}
+
+/// Test migration of a database without schema version.
+#[test]
+fn test_migrate_0() {
+    let mut database = serde_json::json!({
+        "version": "0.8.1",
+        "jobs": [{
+            "start": "2023-03-04T15:25:00Z",
+            "end": "2023-03-04T15:34:00Z",
+            "message": "Did some nice work",
+            "tags": []
+        }],
+        "configuration": {
+            "base": { "resolution": 0.5, "pay": 100.0, "max_hours": 8 },
+            "tags": { "meeting": { "resolution": 0.25, "pay": 80.0, "max_hours": null } }
+        }
+    });
+    assert_eq!(migrate(&mut database).unwrap(), 0);
+    assert_eq!(database["schema"], SCHEMA);
+    assert_eq!(database["jobs"][0]["deleted"], Value::Null);
+    assert_eq!(database["configuration"]["base"]["rate"], 100.0);
+    assert_eq!(database["configuration"]["tags"]["meeting"]["rate"], 80.0);
+    assert!(database["configuration"]["base"].get("pay").is_none());
+    assert!(database["jobs"][0]["id"].is_string());
+}
+
+/// Test refusing databases with newer schema.
+#[test]
+fn test_migrate_too_new() {
+    let mut database = serde_json::json!({
+        "version": "99.0.0",
+        "schema": SCHEMA + 1,
+        "jobs": [],
+    });
+    assert!(matches!(
+        migrate(&mut database),
+        Err(Error::SchemaTooNew(schema, version)) if schema == SCHEMA + 1 && version == "99.0.0"
+    ));
+}
+
+/// Test adding IDs to jobs and to the journal.
+#[test]
+fn test_migrate_1() {
+    let job = serde_json::json!({
+        "start": "2023-03-04T15:25:00Z",
+        "end": null,
+        "message": null,
+        "tags": [],
+        "deleted": null
+    });
+    let mut database = serde_json::json!({
+        "version": "0.10.5",
+        "schema": 1,
+        "jobs": [job],
+        "configuration": { "base": {}, "tags": {} },
+        "journal": {
+            "entries": [{
+                "time": "2023-03-04T15:25:00Z",
+                "summary": "Start job",
+                "changes": [{ "Job": { "pos": 0, "before": null, "after": job } }]
+            }],
+            "undone": 0
+        }
+    });
+    assert_eq!(migrate(&mut database).unwrap(), 1);
+    assert!(database["jobs"][0]["id"].is_string());
+    let journal_job = &database["journal"]["entries"][0]["changes"][0]["Job"]["after"];
+    assert_eq!(journal_job["id"], database["jobs"][0]["id"]);
+    assert_eq!(journal_job["invoice"], Value::Null);
+    assert_eq!(journal_job["locked"], false);
+}
+
+/// Test adding missing invoice numbers to jobs.
+#[test]
+fn test_migrate_2() {
+    let mut database = serde_json::json!({
+        "version": "0.11.0",
+        "schema": 2,
+        "jobs": [{
+            "id": "3f2a",
+            "start": "2023-03-04T15:25:00Z",
+            "end": null,
+            "message": null,
+            "tags": [],
+            "deleted": null
+        }],
+    });
+    assert_eq!(migrate(&mut database).unwrap(), 2);
+    assert_eq!(database["jobs"][0]["invoice"], Value::Null);
+    assert_eq!(database["jobs"][0]["locked"], false);
+}
@@ -0,0 +1,118 @@
+//! Search for text or regular expressions within job messages.
+
+use regex::{Regex, RegexBuilder};
+
+/// Search for a text (case-insensitive) or a regular expression within messages.
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// Searched text or regular expression like it was entered by the user.
+    pattern: String,
+    /// `true` if `pattern` is a regular expression.
+    is_regex: bool,
+    /// Compiled search.
+    regex: Regex,
+}
+
+impl Search {
+    /// Create search for a text which ignores case.
+    pub fn text(text: &str) -> Self {
+        Self {
+            pattern: text.into(),
+            is_regex: false,
+            regex: RegexBuilder::new(&regex::escape(text))
+                .case_insensitive(true)
+                .build()
+                .unwrap(),
+        }
+    }
+    /// Create search for a regular expression.
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: pattern.into(),
+            is_regex: true,
+            regex: Regex::new(pattern)?,
+        })
+    }
+    /// Return `true` if `message` contains any match.
+    pub fn matches(&self, message: &str) -> bool {
+        self.regex.is_match(message)
+    }
+    /// Return the byte ranges of all (non-empty) matches within `text`.
+    fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+impl PartialEq for Search {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.is_regex == other.is_regex
+    }
+}
+
+impl std::fmt::Display for Search {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_regex {
+            write!(f, "/{}/", self.pattern)
+        } else {
+            write!(f, "\"{}\"", self.pattern)
+        }
+    }
+}
+
+/// Mark all matches of any of the `searches` within `text` by using `mark`.
+pub fn highlight<F>(text: &str, searches: &[Search], mark: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    // collect matches of all searches and join overlapping ones
+    let mut matches: Vec<(usize, usize)> = searches
+        .iter()
+        .flat_map(|search| search.find(text))
+        .collect();
+    matches.sort();
+    let mut joined: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in matches {
+        match joined.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => joined.push((start, end)),
+        }
+    }
+    let mut result = String::new();
+    let mut pos = 0;
+    for (start, end) in joined {
+        result += &text[pos..start];
+        result += &mark(&text[start..end]);
+        pos = end;
+    }
+    result + &text[pos..]
+}
+
+/// Test searching and highlighting.
+#[test]
+fn test_search() {
+    let text = Search::text("Login");
+    assert!(text.matches("fixed the login bug"));
+    assert!(!text.matches("fixed the logout bug"));
+    assert_eq!(Search::text("a.b").find("axb a.b"), vec![(4, 7)]);
+
+    let regex = Search::regex(r"log(in|out)").unwrap();
+    assert!(regex.matches("fixed the logout bug"));
+    assert!(Search::regex("log(in").is_err());
+
+    assert_eq!(
+        highlight(
+            "login and logout",
+            &[regex, Search::text("in and")],
+            |m| format!("[{m}]")
+        ),
+        "[login and] [logout]"
+    );
+    assert_eq!(
+        highlight("no match", &[text], |m| format!("[{m}]")),
+        "no match"
+    );
+}
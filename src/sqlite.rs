@@ -0,0 +1,488 @@
+//! SQLite storage of a *jobber* database (feature `sqlite`).
+//!
+//! Jobs are stored in table `jobs` (by position) and their tags in table `tags`.
+//! Journal entries are stored as JSON in table `journal` (by sequence number).
+//! Configuration is stored as JSON within table `meta` together with version, schema and the number of undone journal entries.
+//! Other than the JSON storage this one updates only the jobs and journal entries which were appended or changed.
+
+use super::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+/// SQL statements which create the tables of an empty database.
+const CREATE: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS jobs (
+        pos INTEGER PRIMARY KEY,
+        id TEXT NOT NULL,
+        start TEXT NOT NULL,
+        end TEXT,
+        message TEXT,
+        deleted TEXT,
+        invoice INTEGER,
+        locked INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS tags (
+        pos INTEGER NOT NULL,
+        idx INTEGER NOT NULL,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (pos, idx)
+    );
+    CREATE TABLE IF NOT EXISTS journal (
+        seq INTEGER PRIMARY KEY,
+        entry TEXT NOT NULL
+    );";
+
+/// Upgrade of the tables from one schema version to the next.
+type Migration = fn(&Transaction) -> Result<(), Error>;
+
+/// Migrations by schema version they upgrade from (see [schema::migrate]).
+///
+/// SQLite databases were introduced with schema `1` so there is nothing to upgrade from `0`.
+const MIGRATIONS: [Migration; schema::SCHEMA as usize] = [
+    |_| Ok(()),
+    migrate_1_to_2,
+    migrate_2_to_3,
+    migrate_3_to_4,
+    migrate_4_to_5,
+];
+
+/// Upgrade from schema `1` to `2` by adding unique job IDs.
+fn migrate_1_to_2(tx: &Transaction) -> Result<(), Error> {
+    tx.execute_batch("ALTER TABLE jobs ADD COLUMN id TEXT NOT NULL DEFAULT ''")?;
+    let count: usize = tx.query_row("SELECT COUNT(*) FROM jobs", [], |row| row.get(0))?;
+    let ids: Vec<serde_json::Value> = (0..count).map(|_| JobId::new().as_str().into()).collect();
+    let mut statement = tx.prepare("UPDATE jobs SET id = ?1 WHERE pos = ?2")?;
+    for (pos, id) in ids.iter().enumerate() {
+        statement.execute(params![id.as_str(), pos])?;
+    }
+    migrate_journal(tx, |journal| schema::add_journal_ids(journal, &ids))
+}
+
+/// Upgrade from schema `2` to `3` by adding invoice numbers.
+fn migrate_2_to_3(tx: &Transaction) -> Result<(), Error> {
+    tx.execute_batch("ALTER TABLE jobs ADD COLUMN invoice INTEGER")?;
+    migrate_journal(tx, |journal| {
+        schema::add_journal_field(journal, "invoice", serde_json::Value::Null)
+    })
+}
+
+/// Upgrade from schema `3` to `4` by adding locks.
+fn migrate_3_to_4(tx: &Transaction) -> Result<(), Error> {
+    tx.execute_batch("ALTER TABLE jobs ADD COLUMN locked INTEGER NOT NULL DEFAULT 0")?;
+    migrate_journal(tx, |journal| {
+        schema::add_journal_field(journal, "locked", serde_json::Value::Bool(false))
+    })
+}
+
+/// Upgrade from schema `4` to `5` by moving the journal from table `meta` into entries of table `journal`.
+fn migrate_4_to_5(tx: &Transaction) -> Result<(), Error> {
+    if let Some(journal) = SqliteStorage::get_meta(tx, "journal")? {
+        let mut journal: serde_json::Value = serde_json::from_str(&journal).map_err(Error::Json)?;
+        let mut insert = tx.prepare("INSERT INTO journal (seq, entry) VALUES (?1, ?2)")?;
+        if let Some(serde_json::Value::Array(entries)) = journal.get_mut("entries") {
+            for (seq, entry) in entries.iter().enumerate() {
+                insert.execute(params![
+                    seq,
+                    serde_json::to_string(entry).map_err(Error::Json)?
+                ])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('undone', ?1)",
+            [journal["undone"].as_u64().unwrap_or_default().to_string()],
+        )?;
+        tx.execute("DELETE FROM meta WHERE key = 'journal'", [])?;
+    }
+    Ok(())
+}
+
+/// Upgrade the journal (in JSON representation) which is stored in table `meta` up to schema `4`.
+fn migrate_journal(
+    tx: &Transaction,
+    migrate: impl FnOnce(&mut serde_json::Value),
+) -> Result<(), Error> {
+    if let Some(journal) = SqliteStorage::get_meta(tx, "journal")? {
+        let mut journal = serde_json::from_str(&journal).map_err(Error::Json)?;
+        migrate(&mut journal);
+        tx.execute(
+            "UPDATE meta SET value = ?1 WHERE key = 'journal'",
+            [serde_json::to_string(&journal).map_err(Error::Json)?],
+        )?;
+    }
+    Ok(())
+}
+
+/// Database stored within an SQLite file.
+pub struct SqliteStorage {
+    /// Database file name.
+    filename: String,
+    /// Connection to the database (opened on first use).
+    connection: Option<Connection>,
+    /// Configuration (in JSON representation) like it was loaded or stored last time.
+    configuration: Option<String>,
+}
+
+impl SqliteStorage {
+    /// Create storage for the given file.
+    pub fn new(filename: &str) -> Self {
+        Self {
+            filename: filename.into(),
+            connection: None,
+            configuration: None,
+        }
+    }
+    /// Open connection to the database file (and create it if it does not exist yet).
+    fn connection(&mut self) -> Result<&mut Connection, Error> {
+        if self.connection.is_none() {
+            let connection = Connection::open(&self.filename)?;
+            connection.execute_batch(CREATE)?;
+            self.connection = Some(connection);
+        }
+        Ok(self.connection.as_mut().unwrap())
+    }
+    /// Read a value from table `meta`.
+    fn get_meta(connection: &Connection, key: &str) -> Result<Option<String>, Error> {
+        Ok(connection
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+    /// Write version, schema, number of undone journal entries and (if given) configuration into table `meta`.
+    fn write_meta(
+        tx: &Transaction,
+        jobs: &Jobs,
+        configuration: Option<&String>,
+    ) -> Result<(), Error> {
+        let mut statement =
+            tx.prepare("INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)")?;
+        statement.execute(["version", clap::crate_version!()])?;
+        statement.execute(["schema", &schema::SCHEMA.to_string()])?;
+        statement.execute(["undone", &jobs.journal().redoable().to_string()])?;
+        if let Some(configuration) = configuration {
+            statement.execute(["configuration", configuration])?;
+        }
+        Ok(())
+    }
+    /// Remove dropped or changed journal entries and write the ones which were recorded or changed.
+    fn write_journal(tx: &Transaction, journal: &Journal) -> Result<(), Error> {
+        tx.execute(
+            "DELETE FROM journal WHERE seq < ?1 OR seq >= ?2",
+            params![journal.first(), journal.unstored()],
+        )?;
+        let mut insert = tx.prepare("INSERT INTO journal (seq, entry) VALUES (?1, ?2)")?;
+        for (seq, entry) in journal.unstored_entries() {
+            insert.execute(params![
+                seq,
+                serde_json::to_string(entry).map_err(Error::Json)?
+            ])?;
+        }
+        Ok(())
+    }
+    /// Write meta data, journal and the jobs at the given positions within one transaction.
+    ///
+    /// If `rewrite` is `true` all existing jobs get removed before.
+    fn write(
+        &mut self,
+        jobs: &Jobs,
+        positions: impl Iterator<Item = usize>,
+        rewrite: bool,
+    ) -> Result<(), Error> {
+        let configuration = serde_json::to_string(&jobs.configuration).map_err(Error::Json)?;
+        let changed = self.configuration.as_ref() != Some(&configuration);
+        let connection = self.connection()?;
+        let tx = connection.transaction()?;
+        if rewrite {
+            tx.execute("DELETE FROM jobs", [])?;
+            tx.execute("DELETE FROM tags", [])?;
+        }
+        Self::write_meta(&tx, jobs, changed.then_some(&configuration))?;
+        Self::write_journal(&tx, jobs.journal())?;
+        Self::write_jobs(&tx, jobs, positions)?;
+        tx.commit()?;
+        self.configuration = Some(configuration);
+        Ok(())
+    }
+    /// Write the jobs at the given positions (replacing existing ones).
+    fn write_jobs(
+        tx: &Transaction,
+        jobs: &Jobs,
+        positions: impl Iterator<Item = usize>,
+    ) -> Result<(), Error> {
+        let mut insert_job = tx.prepare(
+            "INSERT OR REPLACE INTO jobs (pos, id, start, end, message, deleted, invoice, locked) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let mut delete_tags = tx.prepare("DELETE FROM tags WHERE pos = ?1")?;
+        let mut insert_tag = tx.prepare("INSERT INTO tags (pos, idx, tag) VALUES (?1, ?2, ?3)")?;
+        for pos in positions {
+            let job = &jobs[pos];
+            insert_job.execute(params![
+                pos,
+                job.id.as_str(),
+                job.start.to_rfc3339(),
+                job.end.map(|end| end.to_rfc3339()),
+                job.message,
+                job.deleted().map(|deleted| deleted.to_rfc3339()),
+                job.invoice(),
+                job.is_locked(),
+            ])?;
+            delete_tags.execute([pos])?;
+            for (idx, tag) in job.tags.iter().enumerate() {
+                insert_tag.execute(params![pos, idx, tag])?;
+            }
+        }
+        Ok(())
+    }
+    /// Upgrade tables from the given schema version to the current one.
+    fn migrate(connection: &mut Connection, schema: u32) -> Result<(), Error> {
+        let version = Self::get_meta(connection, "version")?.unwrap_or("?".into());
+        if schema > schema::SCHEMA {
+            return Err(Error::SchemaTooNew(schema, version));
+        }
+        if schema == 0 {
+            return Err(Error::SchemaFormat("missing schema version".into()));
+        }
+        if schema < schema::SCHEMA {
+            let tx = connection.transaction()?;
+            for migration in &MIGRATIONS[schema as usize..] {
+                migration(&tx)?;
+            }
+            tx.execute(
+                "UPDATE meta SET value = ?1 WHERE key = 'schema'",
+                [schema::SCHEMA.to_string()],
+            )?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse optional RFC3339 date and time read from the database.
+fn parse_date_time(date_time: Option<String>) -> Result<Option<DateTime>, Error> {
+    date_time
+        .map(|date_time| DateTime::from_rfc3339(&date_time))
+        .transpose()
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<Jobs, Error> {
+        if !Path::new(&self.filename).exists() {
+            return Err(Error::Io(std::io::ErrorKind::NotFound.into()));
+        }
+        let connection = self.connection()?;
+
+        let schema = match Self::get_meta(connection, "schema")? {
+            Some(schema) => schema
+                .parse::<u32>()
+                .map_err(|_| Error::SchemaFormat(format!("invalid schema version {schema}")))?,
+            // file exists but nothing was saved into it so far
+            None => return Ok(Jobs::new()),
+        };
+        Self::migrate(connection, schema)?;
+
+        let stored_configuration = Self::get_meta(connection, "configuration")?;
+        let configuration = match &stored_configuration {
+            Some(configuration) => serde_json::from_str(configuration).map_err(Error::Json)?,
+            None => Configuration::default(),
+        };
+
+        let undone = match Self::get_meta(connection, "undone")? {
+            Some(undone) => undone
+                .parse::<usize>()
+                .map_err(|_| Error::SchemaFormat(format!("invalid undone entries {undone}")))?,
+            None => 0,
+        };
+        let mut first = None;
+        let mut entries = Vec::new();
+        let mut statement = connection.prepare("SELECT seq, entry FROM journal ORDER BY seq")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            first.get_or_insert(row.get::<_, usize>(0)?);
+            entries.push(serde_json::from_str(&row.get::<_, String>(1)?).map_err(Error::Json)?);
+        }
+        drop(rows);
+        drop(statement);
+        let journal = Journal::restore(first.unwrap_or_default(), entries, undone);
+
+        let mut tags: Vec<TagSet> = Vec::new();
+        let mut statement = connection.prepare("SELECT pos, tag FROM tags ORDER BY pos, idx")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let pos: usize = row.get(0)?;
+            if tags.len() <= pos {
+                tags.resize(pos + 1, TagSet::new());
+            }
+            tags[pos].insert(&row.get::<_, String>(1)?);
+        }
+
+        let mut jobs = Vec::new();
+        let mut statement = connection.prepare(
+            "SELECT pos, id, start, end, message, deleted, invoice, locked FROM jobs ORDER BY pos",
+        )?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let pos: usize = row.get(0)?;
+            if pos != jobs.len() {
+                return Err(Error::SchemaFormat(format!(
+                    "missing job at position {pos}"
+                )));
+            }
+            let mut job = Job::new(
+                DateTime::from_rfc3339(&row.get::<_, String>(2)?)?,
+                parse_date_time(row.get(3)?)?,
+                row.get(4)?,
+                tags.get(pos).cloned(),
+            )?;
+            job.id = JobId::from(row.get::<_, String>(1)?.as_str());
+            job.set_deleted(parse_date_time(row.get(5)?)?);
+            job.set_invoice(row.get(6)?);
+            job.set_locked(row.get(7)?);
+            jobs.push(job);
+        }
+
+        Ok(Jobs::from_parts(jobs, configuration, journal).upgraded_from(schema))
+    }
+    fn save(&mut self, jobs: &Jobs) -> Result<(), Error> {
+        self.write(jobs, 0..jobs.iter().len(), true)
+    }
+    fn append(&mut self, jobs: &Jobs, from: usize) -> Result<(), Error> {
+        self.write(jobs, from..jobs.iter().len(), false)
+    }
+    fn update(&mut self, jobs: &Jobs, positions: &Positions) -> Result<(), Error> {
+        self.write(jobs, positions.iter().cloned(), false)
+    }
+}
+
+/// Test saving, partially updating and loading an SQLite database.
+#[test]
+fn test_sqlite() {
+    let filename = std::env::temp_dir()
+        .join(format!("jobber_test_sqlite_{}.sqlite", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    let _ = std::fs::remove_file(&filename);
+
+    let context = Context::new_test("2023-2-1 12:00");
+    let mut jobs = Jobs::new();
+    jobs._push(
+        Job::new(
+            "2023-2-1 8:00".into(),
+            Some("2023-2-1 9:00".into()),
+            Some("first".into()),
+            Some("a,b".into()),
+        )
+        .unwrap(),
+    );
+    let mut storage = SqliteStorage::new(&filename);
+    jobs.store(&mut storage).unwrap();
+
+    // append one job and delete the first one
+    jobs._push(Job::new("2023-2-1 10:00".into(), None, None, None).unwrap());
+    jobs.store(&mut storage).unwrap();
+    jobs.process(
+        &mut std::io::sink(),
+        &Command::Delete {
+            range: Range::At(vec![0]),
+            tags: None,
+        },
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    jobs.store(&mut storage).unwrap();
+
+    // append another job after a deleted one
+    jobs._push(
+        Job::new(
+            "2023-2-1 6:00".into(),
+            Some("2023-2-1 7:00".into()),
+            Some("third".into()),
+            None,
+        )
+        .unwrap(),
+    );
+    jobs.store(&mut storage).unwrap();
+
+    let loaded = SqliteStorage::new(&filename).load().unwrap();
+    assert_eq!(loaded.iter().len(), 3);
+    assert_eq!(loaded.count(), 2);
+    assert_eq!(loaded[0].tags, TagSet::from("a,b"));
+    assert_eq!(loaded[0].message, Some("first".into()));
+    assert!(loaded[0].is_deleted());
+    assert!(loaded[1].is_open());
+    assert_eq!(loaded[1].id, jobs[1].id);
+    assert_eq!(loaded[2].message, Some("third".into()));
+    assert_eq!(loaded.journal().undoable(), 1);
+
+    // undo deletion which only updates the number of undone journal entries
+    jobs.process(
+        &mut std::io::sink(),
+        &Command::Undo { count: 1 },
+        Checks::omit(),
+        &context,
+    )
+    .unwrap();
+    jobs.store(&mut storage).unwrap();
+    let loaded = SqliteStorage::new(&filename).load().unwrap();
+    assert!(!loaded[0].is_deleted());
+    assert_eq!(loaded.journal().redoable(), 1);
+    let entries: usize = Connection::open(&filename)
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM journal", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(entries, 1);
+
+    std::fs::remove_file(&filename).unwrap();
+}
+
+/// Test moving the journal from table `meta` into its own table when upgrading from schema `4`.
+#[test]
+fn test_migrate_journal() {
+    let filename = std::env::temp_dir()
+        .join(format!("jobber_test_migrate_{}.sqlite", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    let _ = std::fs::remove_file(&filename);
+
+    let connection = Connection::open(&filename).unwrap();
+    connection.execute_batch(CREATE).unwrap();
+    let journal = serde_json::json!({
+        "entries": [{
+            "time": "2023-03-04T15:25:00Z",
+            "summary": "Changed base configuration",
+            "changes": [{ "Configuration": {
+                "before": { "base": Properties::default(), "tags": {} },
+                "after": { "base": Properties::default(), "tags": {} }
+            } }]
+        }],
+        "undone": 1
+    });
+    for (key, value) in [
+        ("version", "0.11.0".to_string()),
+        ("schema", "4".to_string()),
+        ("journal", journal.to_string()),
+    ] {
+        connection
+            .execute(
+                "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+                [key, &value],
+            )
+            .unwrap();
+    }
+    drop(connection);
+
+    let loaded = SqliteStorage::new(&filename).load().unwrap();
+    assert_eq!(loaded.upgraded(), Some(4));
+    assert_eq!(loaded.journal().redoable(), 1);
+    assert!(
+        SqliteStorage::get_meta(&Connection::open(&filename).unwrap(), "journal")
+            .unwrap()
+            .is_none()
+    );
+
+    std::fs::remove_file(&filename).unwrap();
+}
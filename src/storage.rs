@@ -0,0 +1,216 @@
+//! Persistent storage of a *jobber* database.
+//!
+//! By default databases are stored as JSON files (see [JsonStorage]).
+//! When compiled with feature `sqlite` files with extension `.sqlite` or `.db` are stored in an SQLite database (see [SqliteStorage]).
+
+use super::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+/// Backend which loads and saves a *jobber* database.
+pub trait Storage {
+    /// Load the whole database.
+    fn load(&mut self) -> Result<Jobs, Error>;
+    /// Save the whole database.
+    fn save(&mut self, jobs: &Jobs) -> Result<(), Error>;
+    /// Save jobs which were appended beginning at position `from` together with configuration and journal.
+    ///
+    /// Backends which can not update partially just save the whole database.
+    fn append(&mut self, jobs: &Jobs, _from: usize) -> Result<(), Error> {
+        self.save(jobs)
+    }
+    /// Save jobs at the given `positions` which were changed together with configuration and journal.
+    ///
+    /// Backends which can not update partially just save the whole database.
+    fn update(&mut self, jobs: &Jobs, _positions: &Positions) -> Result<(), Error> {
+        self.save(jobs)
+    }
+}
+
+/// Open the storage which fits the extension of the given database file.
+pub fn open(filename: &str, backups: usize) -> Result<Box<dyn Storage>, Error> {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        #[cfg(feature = "sqlite")]
+        Some("sqlite") | Some("db") => Ok(Box::new(SqliteStorage::new(filename))),
+        #[cfg(not(feature = "sqlite"))]
+        Some("sqlite") | Some("db") => Err(Error::StorageUnsupported(filename.into())),
+        _ => Ok(Box::new(JsonStorage::new(filename, backups))),
+    }
+}
+
+/// Adds a version number to the database when serializing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Versioned<T> {
+    /// Version number string.
+    version: String,
+    /// Schema version (see [schema::SCHEMA]).
+    schema: u32,
+    /// Jobber database.
+    #[serde(flatten)]
+    jobs: T,
+}
+
+/// Database stored within a JSON file.
+pub struct JsonStorage {
+    /// Database file name.
+    filename: String,
+    /// Number of backups to keep when saving.
+    backups: usize,
+}
+
+impl JsonStorage {
+    /// Create storage for the given file which keeps `backups` backups when saving.
+    pub fn new(filename: &str, backups: usize) -> Self {
+        Self {
+            filename: filename.into(),
+            backups,
+        }
+    }
+    /// Write database into file and flush it onto disk.
+    fn write(jobs: &Jobs, filename: &str) -> Result<(), Error> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(filename)
+            .map_err(Error::Io)?;
+        let mut writer = BufWriter::new(file);
+        let versioned_jobs = Versioned {
+            version: clap::crate_version!().to_string(),
+            schema: schema::SCHEMA,
+            jobs,
+        };
+        // pretty print when running tests
+        serde_json::to_writer_pretty(&mut writer, &versioned_jobs).map_err(Error::Json)?;
+        let file = writer
+            .into_inner()
+            .map_err(|err| Error::Io(err.into_error()))?;
+        file.sync_all().map_err(Error::Io)
+    }
+    /// Shift existing backups of the database file by one and copy database file into the first backup.
+    /// Backups exceeding the number of `backups` will be overwritten.
+    fn rotate_backups(&self) -> Result<(), Error> {
+        let filename = &self.filename;
+        if self.backups == 0 || !Path::new(filename).exists() {
+            return Ok(());
+        }
+        for n in (1..self.backups).rev() {
+            let backup = format!("{filename}.{n}");
+            if Path::new(&backup).exists() {
+                std::fs::rename(&backup, format!("{filename}.{}", n + 1)).map_err(Error::Io)?;
+            }
+        }
+        std::fs::copy(filename, format!("{filename}.1")).map_err(Error::Io)?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonStorage {
+    /// Load database from file and upgrade it to the current schema version if necessary.
+    fn load(&mut self) -> Result<Jobs, Error> {
+        let filename = &self.filename;
+        let file = File::options()
+            .read(true)
+            .open(filename)
+            .map_err(Error::Io)?;
+        let reader = BufReader::new(file);
+        let mut database: serde_json::Value =
+            serde_json::from_reader(reader).map_err(Error::Json)?;
+        let schema = schema::migrate(&mut database)?;
+        let versioned = serde_json::from_value::<Versioned<Jobs>>(database).map_err(Error::Json)?;
+        Ok(versioned.jobs.loaded().upgraded_from(schema))
+    }
+    /// Save database into file.
+    ///
+    /// The database is written into a temporary file which then replaces the original file.
+    /// Before that the original file is kept as backup and older backups are rotated (`<filename>.1`, `<filename>.2`, …)
+    /// so that at most `backups` of them will remain.
+    fn save(&mut self, jobs: &Jobs) -> Result<(), Error> {
+        let filename = &self.filename;
+        let temp = format!("{filename}.tmp");
+        if let Err(err) = Self::write(jobs, &temp) {
+            // do not leave broken temporary file
+            let _ = std::fs::remove_file(&temp);
+            return Err(err);
+        }
+        self.rotate_backups()?;
+        std::fs::rename(&temp, filename).map_err(Error::Io)?;
+        // make sure renaming survives a crash
+        #[cfg(unix)]
+        if let Some(dir) = Path::new(filename).parent() {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            File::open(dir).and_then(|dir| dir.sync_all())?;
+        }
+        Ok(())
+    }
+}
+
+/// Test selection of storage backend by file extension.
+#[test]
+fn test_open() {
+    assert!(open("jobs.json", 0).is_ok());
+    assert!(open("jobs", 0).is_ok());
+    #[cfg(not(feature = "sqlite"))]
+    assert!(matches!(
+        open("jobs.sqlite", 0),
+        Err(Error::StorageUnsupported(_))
+    ));
+}
+
+/// Test saving with backup rotation.
+#[test]
+fn test_save_backups() {
+    let dir = std::env::temp_dir().join(format!("jobber-test-save-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let filename = dir.join("jobber.json").to_string_lossy().to_string();
+
+    // save database four times with different configurations
+    let mut jobs = Jobs::new();
+    for rate in 1..=4 {
+        jobs.configuration.base.rate = Some(rate as f64);
+        jobs.save(&filename, 2).unwrap();
+    }
+
+    // only two backups with the previous versions shall remain
+    assert_eq!(
+        Jobs::load(&filename).unwrap().configuration.base.rate,
+        Some(4.0)
+    );
+    assert_eq!(
+        Jobs::load(&format!("{filename}.1"))
+            .unwrap()
+            .configuration
+            .base
+            .rate,
+        Some(3.0)
+    );
+    assert_eq!(
+        Jobs::load(&format!("{filename}.2"))
+            .unwrap()
+            .configuration
+            .base
+            .rate,
+        Some(2.0)
+    );
+    assert!(!Path::new(&format!("{filename}.3")).exists());
+    assert!(!Path::new(&format!("{filename}.tmp")).exists());
+
+    // saving into a non-existing directory fails without panic
+    assert!(matches!(
+        jobs.save(&dir.join("missing/jobber.json").to_string_lossy(), 2),
+        Err(Error::Io(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
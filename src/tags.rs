@@ -1,27 +1,25 @@
 //! Static global tag register :/ to manage colorization of tags.
 
 use super::prelude::*;
+use std::sync::Mutex;
 
 /// Tag register.
-static mut TAGS: TagSet = TagSet::new();
+static TAGS: Mutex<TagSet> = Mutex::new(TagSet::new());
 
 /// initialize tag index `TAGS` from a list of jobs
 pub fn init(jobs: &Jobs) {
-    unsafe { TAGS = jobs.tags() }
+    *TAGS.lock().unwrap() = jobs.tags()
 }
 
 /// Update register with job's tags.
 pub fn update(job: &Job) {
-    unsafe { TAGS.insert_many(job.tags.clone()) }
+    TAGS.lock().unwrap().insert_many(job.tags.clone())
 }
 
 /// Decorate tag with color.
 #[cfg(feature = "colors")]
 pub fn format(f: &mut std::fmt::Formatter, tag: &String) -> std::fmt::Result {
-    use termion::{
-        color::*,
-        style,
-    };
+    use termion::{color::*, style};
     write!(f, "{}", style::Bold)?;
     if let Some(position) = position(tag) {
         match position % 11 {
@@ -44,16 +42,13 @@ pub fn format(f: &mut std::fmt::Formatter, tag: &String) -> std::fmt::Result {
     write!(f, "{}{}{}", style::Reset, Fg(Reset), Bg(Reset))
 }
 
-
 #[cfg(not(feature = "colors"))]
 pub fn format(f: &mut std::fmt::Formatter, tag: &String) -> std::fmt::Result {
-    write!(f, "{}", &tag)
+    write!(f, " {} ", &tag)
 }
 
 /// get the position of a tag within the tag index `TAGS` (to assign a color)
 #[cfg(feature = "colors")]
 fn position(tag: &String) -> Option<usize> {
-    unsafe {
-        TAGS.0.iter().position(|t| t == tag)
-    }
+    TAGS.lock().unwrap().0.iter().position(|t| t == tag)
 }
@@ -0,0 +1,159 @@
+//! Timeclock format (of *hledger* and *ledger*) export and import.
+
+use super::prelude::*;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use std::io::{BufRead, BufReader, Read};
+
+/// Account of jobs without tags.
+const DEFAULT_ACCOUNT: &str = "jobber";
+
+/// Export jobs as pairs of clock-in (`i`) and clock-out (`o`) lines.
+///
+/// Tags become the account (separated by `:`) and the message becomes the description.
+/// * `w`: Where the output goes
+/// * `jobs`: Jobs to export
+pub fn export_timeclock<W: std::io::Write>(w: &mut W, jobs: &JobList) -> Result<(), Error> {
+    for (_, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
+        let account = if job.tags.is_empty() {
+            DEFAULT_ACCOUNT.to_string()
+        } else {
+            job.tags.0.join(":")
+        };
+        write!(w, "i {} {account}", job.start.format("%Y-%m-%d %H:%M:%S"))?;
+        if let Some(message) = &job.message {
+            write!(w, "  {}", message.replace('\n', " "))?;
+        }
+        writeln!(w)?;
+        if let Some(end) = job.end {
+            writeln!(w, "o {}", end.format("%Y-%m-%d %H:%M:%S"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Import clock-in (`i`) and clock-out (`o`) lines with times in the given time zone.
+///
+/// A final clock-in without clock-out becomes an open job.
+/// Returns a list of errors (one per line) if any line could not be imported.
+pub fn import_timeclock<R: Read>(reader: R, zone: &Zone) -> Result<Vec<Job>, Vec<String>> {
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    // clock-in which waits for its clock-out
+    let mut open: Option<(DateTime, TagSet, Option<String>)> = None;
+    for (n, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|err| vec![err.to_string()])?;
+        let line = line.trim_end();
+        // skip empty lines and comments
+        if line.is_empty() || line.starts_with([';', '#', '*']) {
+            continue;
+        }
+        let code = line.chars().next().unwrap().len_utf8();
+        let result = match line.split_at(code) {
+            ("i", entry) => match (open.take(), parse_clock_in(entry, zone)) {
+                (None, Ok(clock_in)) => {
+                    open = Some(clock_in);
+                    Ok(())
+                }
+                (Some(_), _) => Err("clock-in while already clocked in".to_string()),
+                (_, Err(err)) => Err(err),
+            },
+            ("o" | "O", entry) => match open.take() {
+                Some((start, tags, message)) => parse_date_time(entry, zone)
+                    .map(|(end, _)| end)
+                    .and_then(|end| {
+                        Job::new(start, Some(end), message, Some(tags))
+                            .map_err(|err| err.to_string())
+                    })
+                    .map(|job| jobs.push(job)),
+                None => Err("clock-out without clock-in".into()),
+            },
+            (code, _) => Err(format!("unsupported entry '{code}'")),
+        };
+        if let Err(err) = result {
+            errors.push(format!("line {}: {err}", n + 1));
+        }
+    }
+    if let Some((start, tags, message)) = open {
+        match Job::new(start, None, message, Some(tags)) {
+            Ok(job) => jobs.push(job),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse clock-in entry like `2023-01-31 08:00:00 client:project  description`.
+fn parse_clock_in(entry: &str, zone: &Zone) -> Result<(DateTime, TagSet, Option<String>), String> {
+    let (start, rest) = parse_date_time(entry, zone)?;
+    // account and description are separated by two spaces or a tab
+    let (account, description) = match rest.find("  ").or_else(|| rest.find('\t')) {
+        Some(pos) => (rest[..pos].trim(), rest[pos..].trim()),
+        None => (rest.trim(), ""),
+    };
+    let mut tags = TagSet::new();
+    if account != DEFAULT_ACCOUNT {
+        for tag in account.split(':').filter(|tag| !tag.is_empty()) {
+            if tag.contains(char::is_whitespace) {
+                return Err(format!("account '{account}' contains white space"));
+            }
+            tags.insert(tag);
+        }
+    }
+    let message = if description.is_empty() {
+        None
+    } else {
+        Some(description.to_string())
+    };
+    Ok((start, tags, message))
+}
+
+/// Parse leading date and time like `2023/01/31 08:00:00` and return the rest of the entry.
+fn parse_date_time<'a>(entry: &'a str, zone: &Zone) -> Result<(DateTime, &'a str), String> {
+    let entry = entry.trim_start();
+    let mut words = entry.splitn(3, ' ');
+    let (date, time) = match (words.next(), words.next()) {
+        (Some(date), Some(time)) => (date, time),
+        _ => return Err("missing date and time".into()),
+    };
+    let date_time = format!("{} {time}", date.replace(['/', '.'], "-"));
+    let naive = NaiveDateTime::parse_from_str(&date_time, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&date_time, "%Y-%m-%d %H:%M"))
+        .map_err(|err| format!("can not parse date '{date} {time}' ({err})"))?;
+    let date_time = zone
+        .convert(&naive)
+        .ok_or_else(|| format!("date '{date} {time}' does not exist in time zone"))?;
+    Ok((date_time, words.next().unwrap_or_default()))
+}
+
+/// Test reading a hledger timeclock file.
+#[test]
+fn test_import_timeclock() {
+    let timeclock = "; my hours\n\
+                     i 2023/01/31 08:00:00 client:project  write some code\n\
+                     o 2023/01/31 10:30:00\n\
+                     i 2023-01-31 11:00 jobber\n\
+                     o 2023-01-31 11:45\n\
+                     i 2023-01-31 12:00:00 client\n";
+    let jobs = import_timeclock(timeclock.as_bytes(), &Zone::Utc).unwrap();
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(jobs[0].start.to_rfc3339(), "2023-01-31T08:00:00+00:00");
+    assert_eq!(
+        jobs[0].end.unwrap().to_rfc3339(),
+        "2023-01-31T10:30:00+00:00"
+    );
+    assert_eq!(jobs[0].tags, TagSet::from("client,project"));
+    assert_eq!(jobs[0].message, Some("write some code".into()));
+    assert!(jobs[1].tags.is_empty());
+    assert!(jobs[2].is_open());
+
+    let errors = import_timeclock("o 2023/01/31 10:30:00\n".as_bytes(), &Zone::Utc).unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["line 1: clock-out without clock-in".to_string()]
+    );
+}
@@ -0,0 +1,178 @@
+//! Timewarrior data format export and import.
+
+use super::prelude::*;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use std::io::{BufRead, BufReader, Read};
+
+/// Format of date and time within Timewarrior data.
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Export jobs as Timewarrior intervals like `inc <start> - <end> # <tags> # "<message>"`.
+/// * `w`: Where the output goes
+/// * `jobs`: Jobs to export
+pub fn export_timewarrior<W: std::io::Write>(w: &mut W, jobs: &JobList) -> Result<(), Error> {
+    for (_, job) in jobs.iter().sorted_by(|l, r| l.1.cmp(r.1)) {
+        write!(w, "inc {}", job.start.format_utc(DATE_FORMAT))?;
+        if let Some(end) = job.end {
+            write!(w, " - {}", end.format_utc(DATE_FORMAT))?;
+        }
+        if !job.tags.is_empty() {
+            write!(w, " #")?;
+            for tag in &job.tags.0 {
+                if tag
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "-_.:".contains(c))
+                {
+                    write!(w, " {tag}")?;
+                } else {
+                    write!(w, " {}", quote(tag))?;
+                }
+            }
+        }
+        if let Some(message) = &job.message {
+            if job.tags.is_empty() {
+                write!(w, " #")?;
+            }
+            write!(w, " # {}", quote(message))?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Put text into quotes and escape quotes, backslashes and line breaks.
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Split line into white space separated words which may be quoted (flagged with `true`).
+fn split(line: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut word = String::new();
+        if c == '"' {
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => word.push('\n'),
+                        Some(c) => word.push(c),
+                        None => return Err("missing closing quote".into()),
+                    },
+                    Some(c) => word.push(c),
+                    None => return Err("missing closing quote".into()),
+                }
+            }
+            words.push((word, true));
+        } else {
+            word.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                word.push(c);
+            }
+            words.push((word, false));
+        }
+    }
+    Ok(words)
+}
+
+/// Import Timewarrior intervals (like in the files of Timewarrior's `data` directory).
+///
+/// Returns a list of errors (one per line) if any line could not be imported.
+pub fn import_timewarrior<R: Read>(reader: R) -> Result<Vec<Job>, Vec<String>> {
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for (n, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|err| vec![err.to_string()])?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_interval(&line) {
+            Ok(job) => jobs.push(job),
+            Err(err) => errors.push(format!("line {}: {err}", n + 1)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Create a job from an interval line.
+fn parse_interval(line: &str) -> Result<Job, String> {
+    let mut words = split(line)?.into_iter().peekable();
+    if words.next().filter(|(word, _)| word == "inc").is_none() {
+        return Err("interval must start with 'inc'".into());
+    }
+    let start = parse_date_time(&words.next().ok_or("missing start")?.0)?;
+    let end = if words
+        .next_if(|(word, quoted)| word == "-" && !quoted)
+        .is_some()
+    {
+        Some(parse_date_time(&words.next().ok_or("missing end")?.0)?)
+    } else {
+        None
+    };
+    let mut tags = TagSet::new();
+    let mut message = None;
+    if words
+        .next_if(|(word, quoted)| word == "#" && !quoted)
+        .is_some()
+    {
+        while let Some((tag, _)) = words.next_if(|(word, quoted)| word != "#" || *quoted) {
+            if tag.contains(char::is_whitespace) {
+                return Err(format!("tag '{tag}' contains white space"));
+            }
+            tags.insert(&tag);
+        }
+        if words.next().is_some() {
+            message = Some(words.map(|(word, _)| word).join(" "));
+        }
+    } else if let Some((word, _)) = words.next() {
+        return Err(format!("unexpected '{word}'"));
+    }
+    Job::new(start, end, message, Some(tags)).map_err(|err| err.to_string())
+}
+
+/// Parse date and time like `20230131T080000Z` (local time if `Z` is missing).
+fn parse_date_time(date_time: &str) -> Result<DateTime, String> {
+    let naive = NaiveDateTime::parse_from_str(date_time.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map_err(|err| format!("can not parse date '{date_time}' ({err})"))?;
+    if date_time.ends_with('Z') {
+        Zone::Utc
+    } else {
+        Zone::Local
+    }
+    .convert(&naive)
+    .ok_or_else(|| format!("date '{date_time}' does not exist in time zone"))
+}
+
+/// Test reading intervals written by Timewarrior.
+#[test]
+fn test_import_timewarrior() {
+    let data = "inc 20230131T080000Z - 20230131T103000Z # client \"meeting\" # \"talk about \\\"costs\\\"\"\n\
+                inc 20230131T110000Z - 20230131T114500Z # # \"no tags\"\n\
+                inc 20230131T120000Z\n";
+    let jobs = import_timewarrior(data.as_bytes()).unwrap();
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(jobs[0].start.to_rfc3339(), "2023-01-31T08:00:00+00:00");
+    assert_eq!(jobs[0].tags, TagSet::from("client,meeting"));
+    assert_eq!(jobs[0].message, Some("talk about \"costs\"".into()));
+    assert!(jobs[1].tags.is_empty());
+    assert_eq!(jobs[1].message, Some("no tags".into()));
+    assert!(jobs[2].is_open());
+
+    let errors = import_timewarrior("exc 20230131T080000Z\ninc 2023\n".as_bytes()).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].starts_with("line 2:"));
+}